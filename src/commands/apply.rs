@@ -1,19 +1,79 @@
-use console::style;
-use dialoguer::{Input, theme::ColorfulTheme};
+use std::{collections::HashMap, path::PathBuf};
 
-use crate::core::{header::get_header, storage::Storage};
+use dialoguer::theme::ColorfulTheme;
+use serde::Deserialize;
 
-pub fn run(name: String, store: &mut Storage, input_theme: &ColorfulTheme) {
-    if let Some(mut template) = store.get(&name).cloned() {
+use crate::{
+    core::{
+        header::{get_header, value_input},
+        output::Output,
+        spec::{is_interactive, read_spec},
+        storage::Storage,
+    },
+    error::{Error, Result},
+};
+
+#[allow(clippy::too_many_arguments)]
+pub fn run(
+    name: String,
+    set: Vec<(String, String)>,
+    from_file: Option<PathBuf>,
+    lang: Option<String>,
+    comment: Option<String>,
+    store: &mut Storage,
+    output: &Output,
+    input_theme: &ColorfulTheme,
+) -> Result<()> {
+    let mut template = store
+        .get(&name)
+        .cloned()
+        .ok_or_else(|| Error::not_found(&name))?;
+    template.set_comment(lang.as_deref(), comment.as_deref())?;
+
+    // values from the spec file, overridden by --set
+    let mut values: Vec<(String, String)> = match from_file {
+        Some(path) => read_spec::<HashMap<String, Value>>(&path)?
+            .into_iter()
+            .map(|(key, value)| (key, value.into_string()))
+            .collect(),
+        None => vec![],
+    };
+    values.extend(set);
+    let mut supplied = template.set_values(values)?;
+
+    // prompt only for the fields not given on the command line
+    if is_interactive() {
         for field in &mut template.fields {
-            let input = Input::with_theme(input_theme)
-                .with_prompt(field.key.clone())
-                .interact()
-                .unwrap();
-            field.default_value = input;
+            if field.kind.is_separator() || supplied.contains(&field.key) {
+                continue;
+            }
+            field.default_value = value_input(field, input_theme)?;
+            supplied.push(field.key.clone());
+        }
+    }
+    let missing = template.missing_required(&supplied);
+    if !missing.is_empty() {
+        return Err(Error::Validation(format!(
+            "missing required fields: {}",
+            missing.join(", ")
+        )));
+    }
+    get_header(&template, None, output)
+}
+
+/// A field value in a spec file, lists are one item per line
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Value {
+    Text(String),
+    List(Vec<String>),
+}
+
+impl Value {
+    fn into_string(self) -> String {
+        match self {
+            Value::Text(text) => text,
+            Value::List(items) => items.join("\n"),
         }
-        get_header(&template);
-    } else {
-        println!("{}", style("Header not found").red().bold());
     }
 }
@@ -0,0 +1,61 @@
+use std::{fs, path::PathBuf};
+
+use console::style;
+
+use crate::{
+    commands::FileTemplates,
+    core::{
+        placeholder::Context,
+        source::{HeaderStatus, header_status, source_files},
+        storage::Storage,
+    },
+    error::{Error, Result},
+};
+
+pub fn run(
+    paths: Vec<PathBuf>,
+    name: Option<String>,
+    lang: Option<String>,
+    comment: Option<String>,
+    store: &Storage,
+) -> Result<()> {
+    // files without a known comment syntax can't hold a header
+    let mut templates = FileTemplates::new(store, name, lang, comment, true)?;
+
+    let mut checked = 0;
+    let mut problems = 0;
+    for path in source_files(&paths)? {
+        let Some(template) = templates.get(&path) else {
+            continue;
+        };
+        let context = Context::new(Some(&path));
+        let Ok(text) = fs::read_to_string(&path) else {
+            continue;
+        };
+
+        checked += 1;
+        match header_status(&template, &context, &text) {
+            HeaderStatus::Ok => {}
+            HeaderStatus::Missing => {
+                problems += 1;
+                println!("{} {}", style("Missing").red().bold(), path.display());
+            }
+            HeaderStatus::Stale => {
+                problems += 1;
+                println!("{} {}", style("Stale").yellow().bold(), path.display());
+            }
+        }
+    }
+
+    println!("{} files checked, {} with problems", checked, problems);
+    if templates.failed > 0 {
+        Err(Error::Io(format!(
+            "{} files could not be processed",
+            templates.failed
+        )))
+    } else if problems > 0 {
+        Err(Error::Check(problems))
+    } else {
+        Ok(())
+    }
+}
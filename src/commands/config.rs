@@ -0,0 +1,120 @@
+use std::{env, fs, io, path::Path, process::Command};
+
+use console::style;
+use dialoguer::{Confirm, theme::ColorfulTheme};
+
+use crate::{
+    commands::ConfigAction,
+    core::{config::Config, spec::is_interactive, storage::Storage},
+    error::{Error, Result},
+};
+
+pub fn run(action: ConfigAction, input_theme: &ColorfulTheme) -> Result<()> {
+    match action {
+        ConfigAction::Get { key } => {
+            let value = Config::read()?.get(&key)?;
+            match value {
+                Some(value) => {
+                    println!("{}", value);
+                    Ok(())
+                }
+                None => Err(Error::not_found(&key)),
+            }
+        }
+        ConfigAction::Set { key, value } => {
+            // template names must exist when they are set
+            if key.starts_with("templates.") && Storage::new()?.get(&value).is_none() {
+                return Err(Error::not_found(&value));
+            }
+            let mut config = Config::read()?;
+            config.set(&key, value)?;
+            config.save()
+        }
+        ConfigAction::Unset { key } => {
+            let mut config = Config::read()?;
+            if !config.unset(&key)? {
+                return Err(Error::not_found(&key));
+            }
+            config.save()
+        }
+        ConfigAction::List => {
+            let config = Config::read()?;
+            for (key, value) in config.list() {
+                println!("{} = {}", key, value);
+            }
+            if !config.rules.is_empty() {
+                eprintln!(
+                    "{} rules are set, see `header config edit`",
+                    config.rules.len()
+                );
+            }
+            Ok(())
+        }
+        ConfigAction::Path => {
+            println!("{}", Config::path()?.display());
+            Ok(())
+        }
+        ConfigAction::Edit => edit(input_theme),
+    }
+}
+
+/// Open the config file in `$VISUAL` or `$EDITOR` until it parses,
+/// or put the previous file back
+fn edit(input_theme: &ColorfulTheme) -> Result<()> {
+    let path = Config::path()?;
+    let previous = match fs::read_to_string(&path) {
+        Ok(text) => Some(text),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(Error::io(&path, e)),
+    };
+    loop {
+        let result = open_editor(&path).and_then(|_| Config::read());
+        let Err(e) = result else {
+            println!("{}", style("Saved").green().bold());
+            return Ok(());
+        };
+        if is_interactive() {
+            e.report();
+            let again = Confirm::with_theme(input_theme)
+                .with_prompt("Edit again? (no restores the previous file)")
+                .default(true)
+                .interact()?;
+            if again {
+                continue;
+            }
+        }
+        restore(&path, previous.as_deref())?;
+        eprintln!("{} {}", style("Restored").yellow().bold(), path.display());
+        return Err(e);
+    }
+}
+
+fn open_editor(path: &Path) -> Result<()> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    // the editor may come with arguments, e.g. `code --wait`
+    let mut args = editor.split_whitespace();
+    let program = args.next().unwrap_or("vi");
+    let status = Command::new(program)
+        .args(args)
+        .arg(path)
+        .status()
+        .map_err(|e| Error::Io(format!("{}: {}", program, e)))?;
+    if !status.success() {
+        return Err(Error::Io(format!("{} exited with {}", program, status)));
+    }
+    Ok(())
+}
+
+/// Write back `previous`, or remove the file when there was none
+fn restore(path: &Path, previous: Option<&str>) -> Result<()> {
+    let result = match previous {
+        Some(text) => fs::write(path, text),
+        None => fs::remove_file(path).or_else(|e| match e.kind() {
+            io::ErrorKind::NotFound => Ok(()),
+            _ => Err(e),
+        }),
+    };
+    result.map_err(|e| Error::io(path, e))
+}
@@ -1,16 +1,57 @@
-use std::cmp;
+use std::path::PathBuf;
 
-use console::style;
+use crate::{
+    core::{header::get_header, output::Output, spec::parse_key_value, storage::Storage},
+    error::{Error, Result},
+};
 
-use crate::core::{header::get_header, storage::Storage};
+pub fn run(
+    values: Vec<String>,
+    path: Option<PathBuf>,
+    lang: Option<String>,
+    comment: Option<String>,
+    store: &Storage,
+    output: &Output,
+) -> Result<()> {
+    let mut default_template = match &path {
+        Some(path) => store.template_for(None, path)?.clone(),
+        None => store
+            .get_default()
+            .cloned()
+            .ok_or_else(|| Error::NotFound("default header".to_string()))?,
+    };
+    if let Some(path) = &path
+        && lang.is_none()
+        && comment.is_none()
+        && let Some(comment_style) = store.comment_for(path)
+    {
+        default_template.comment = Some(comment_style);
+    }
+    default_template.set_comment(lang.as_deref(), comment.as_deref())?;
 
-pub fn run(values: Vec<String>, store: &mut Storage) {
-    if let Some(mut default_template) = store.get_default().cloned() {
-        for i in 0..cmp::min(default_template.fields.len(), values.len()) {
-            default_template.fields[i].default_value = values[i].clone();
+    // `key=value`, or plain values matched by position
+    let mut named = vec![];
+    for (i, value) in values.into_iter().enumerate() {
+        if value.contains('=') {
+            named.push(parse_key_value(&value).map_err(Error::Validation)?);
+        } else if let Some(field) = default_template.value_fields().nth(i) {
+            named.push((field.key.clone(), value));
+        } else {
+            return Err(Error::Validation(format!(
+                "too many values, `{}` has {} fields",
+                default_template.name,
+                default_template.value_fields().count()
+            )));
         }
-        get_header(&default_template);
-    } else {
-        println!("{}", style("Header not found").red().bold());
     }
+
+    let supplied = default_template.set_values(named)?;
+    let missing = default_template.missing_required(&supplied);
+    if !missing.is_empty() {
+        return Err(Error::Validation(format!(
+            "missing required fields: {}",
+            missing.join(", ")
+        )));
+    }
+    get_header(&default_template, path.as_deref(), output)
 }
@@ -1,39 +1,42 @@
 use console::style;
 use dialoguer::{Confirm, theme::ColorfulTheme};
 
-use crate::core::storage::Storage;
+use crate::{
+    core::{spec::require_interactive, storage::Storage},
+    error::Result,
+};
 
-pub fn run(all: bool, name: Option<String>, store: &mut Storage, input_theme: &ColorfulTheme) {
+pub fn run(
+    all: bool,
+    name: Option<String>,
+    yes: bool,
+    store: &mut Storage,
+    input_theme: &ColorfulTheme,
+) -> Result<()> {
+    if !yes {
+        require_interactive("use --yes to delete without confirmation")?;
+    }
     if all {
-        let confirm: bool = Confirm::with_theme(input_theme)
-            .with_prompt(format!("{}", style("Delete all headers").red().bold()))
-            .interact()
-            .unwrap();
+        let confirm: bool = yes
+            || Confirm::with_theme(input_theme)
+                .with_prompt(format!("{}", style("Delete all headers").red().bold()))
+                .interact()?;
         if confirm {
-            if let Err(e) = store.delete_all() {
-                println!("{}: {}", style("error").red().bold(), e);
-                return;
-            }
+            store.delete_all()?;
         }
     } else if let Some(name) = name {
-        let confirm: bool = Confirm::with_theme(input_theme)
-            .with_prompt(format!(
-                "{} header `{}`?",
-                style("Delete").red().bold(),
-                name
-            ))
-            .interact()
-            .unwrap();
+        let confirm: bool = yes
+            || Confirm::with_theme(input_theme)
+                .with_prompt(format!(
+                    "{} header `{}`?",
+                    style("Delete").red().bold(),
+                    name
+                ))
+                .interact()?;
 
         if confirm {
-            if let Err(e) = store.delete(&name) {
-                println!("{}: {}", style("error").red().bold(), e);
-                return;
-            }
-        } else {
-            return;
+            store.delete(&name)?;
         }
-    } else {
-        return;
     }
+    Ok(())
 }
@@ -1,161 +1,317 @@
+use std::path::PathBuf;
+
 use console::style;
 use dialoguer::{Confirm, Input, Select, theme::ColorfulTheme};
 
-use crate::core::{
-    header::border_input,
-    storage::Storage,
-    template::{Alignment, Border, Template},
+use crate::{
+    core::{
+        comment::CommentStyle,
+        header::{border_select, kind_select},
+        spec::{read_spec, require_interactive},
+        storage::Storage,
+        template::{Alignment, Border, Field, FieldKind, Template},
+        text,
+    },
+    error::Error,
 };
 
+#[allow(clippy::too_many_arguments)]
 pub fn run(
     header_name: String,
     name: Option<String>,
     spacing: bool,
-    border: bool,
+    border: Option<Option<String>>,
     fields: bool,
+    comment: bool,
+    separator: Option<String>,
+    key_width: Option<u8>,
+    from_file: Option<PathBuf>,
     store: &mut Storage,
     input_theme: &ColorfulTheme,
-) {
-    if let Some(template) = store.get_mut(&header_name) {
-        // edit name
-        if name.is_some() {
-            template.name = name.unwrap();
-        }
-        // edit spacing
-        else if spacing {
-            let items = vec!["space_before", "space_after"];
-            let selection = Select::with_theme(input_theme)
-                .with_prompt("Select spacing")
-                .items(items)
+) -> Result<(), Error> {
+    if let Some(path) = from_file {
+        return from_spec(header_name, path, store);
+    }
+    if spacing || matches!(border, Some(None)) || fields || comment {
+        require_interactive("use --name or --from-file to edit a header")?;
+    }
+
+    let template = store.get_mut(&header_name)?;
+    // edit name
+    if let Some(name) = name {
+        template.name = name;
+    }
+    // edit separator
+    else if let Some(separator) = separator {
+        template.separator = separator;
+    }
+    // edit key column width, 0 goes back to the widest key
+    else if let Some(key_width) = key_width {
+        template.key_width = Some(key_width).filter(|width| *width > 0);
+    }
+    // edit spacing
+    else if spacing {
+        let items = vec!["space_before", "space_after"];
+        let selection = Select::with_theme(input_theme)
+            .with_prompt("Select spacing")
+            .items(items)
+            .default(0)
+            .interact()?;
+        let space = Input::with_theme(input_theme)
+            .with_prompt("New space")
+            .default(Template::SPACE_HEIGHT)
+            .interact()?;
+        match selection {
+            0 => {
+                template.space_before = space;
+            }
+            1 => template.space_after = space,
+            _ => {}
+        };
+    }
+    // edit border
+    else if let Some(preset) = border {
+        template.border = match preset {
+            Some(preset) => Some(Border::from_preset(&preset)?),
+            None => border_select(input_theme)?,
+        };
+    }
+    // edit fields
+    else if fields {
+        let fields = &mut template.fields;
+        let length = template.length;
+        loop {
+            let mut items: Vec<String> = fields
+                .iter()
+                .map(|field| match field.kind.is_separator() {
+                    true => format!("[{}] {}", field.kind.name(), field.label()),
+                    false => field.key.clone(),
+                })
+                .collect();
+            items.push("+ add separator".to_string());
+            let Some(field_idx) = Select::with_theme(input_theme)
+                .with_prompt("Select a field")
+                .items(&items)
                 .default(0)
-                .interact()
-                .unwrap();
-            let space = Input::with_theme(input_theme)
-                .with_prompt("New space")
-                .default(Template::SPACE_HEIGHT)
-                .interact()
-                .unwrap();
-            match selection {
-                0 => {
-                    template.space_before = space;
-                }
-                1 => template.space_after = space,
-                _ => {}
+                .interact_opt()?
+            else {
+                break;
             };
-        }
-        // edit border
-        else if border {
-            let left = border_input("Left", input_theme);
-            let top = border_input("Top", input_theme);
-            let right = border_input("Right", input_theme);
-            let bottom = border_input("Bottom", input_theme);
-            if left.is_some() || top.is_some() || right.is_some() || bottom.is_some() {
-                template.border = Some(Border {
-                    left,
-                    top,
-                    right,
-                    bottom,
-                });
-            } else {
-                template.border = None;
+            if field_idx == fields.len() {
+                add_separator(fields, input_theme)?;
+                continue;
             }
-        }
-        // edit fields
-        else if fields {
-            let fields = &mut template.fields;
-            let length = template.length;
-            if fields.len() <= 0 {
-                println!("This header hasn't got any fields");
-                return;
+            if fields[field_idx].kind.is_separator() {
+                edit_separator(fields, field_idx, input_theme)?;
+                continue;
             }
-            let keys = &fields
-                .iter()
-                .map(|field| field.key.clone())
-                .collect::<Vec<String>>();
-            loop {
-                let field_idx = Select::with_theme(input_theme)
-                    .with_prompt("Select a field")
-                    .items(keys)
-                    .default(0)
-                    .interact_opt()
-                    .unwrap();
-                if field_idx.is_none() {
-                    break;
-                }
-                let field_idx = field_idx.unwrap();
-                let field = &mut fields[field_idx];
-                loop {
-                    let items = ["key", "default_value", "align", "key_visible"];
-                    let selection = Select::with_theme(input_theme)
-                        .with_prompt("Edit")
-                        .items(items)
-                        .default(0)
-                        .interact_opt()
-                        .unwrap();
-                    if selection.is_none() {
-                        break;
-                    }
-                    match selection.unwrap() {
-                        0 => {
-                            let new_key = Input::with_theme(input_theme)
+            let field = &mut fields[field_idx];
+            let items = [
+                "key",
+                "default_value",
+                "align",
+                "key_visible",
+                "required",
+                "padding",
+                "kind",
+                "group",
+            ];
+            while let Some(selection) = Select::with_theme(input_theme)
+                .with_prompt("Edit")
+                .items(items)
+                .default(0)
+                .interact_opt()?
+            {
+                match selection {
+                    0 => {
+                        let new_key = Input::with_theme(input_theme)
                                 .with_prompt("New key")
                                 .default(field.key.clone())
                                 .validate_with(|input: &String| -> Result<(), String> {
-                                    if input.len() > (length / 2) as usize {
+                                    if text::width(input) > (length / 2) as usize {
                                         Err("The length of the key must be less than half of the total length".to_string())
                                     } else {
                                         Ok(())
                                     }
                                 })
-                                .interact()
-                                .unwrap();
-                            field.key = new_key;
-                        }
-                        1 => {
-                            let new_default_value = Input::with_theme(input_theme)
-                                .with_prompt("New default value")
-                                .default(field.default_value.clone())
-                                .interact()
-                                .unwrap();
-                            field.default_value = new_default_value;
-                        }
-                        2 => {
-                            let is_center = Confirm::with_theme(input_theme)
-                                .with_prompt("Center text")
-                                .default(false)
-                                .interact()
-                                .unwrap();
-                            field.align = if is_center {
-                                Alignment::Center
-                            } else {
-                                Alignment::Left
-                            };
-                        }
-                        3 => {
-                            let key_visible = Confirm::with_theme(input_theme)
-                                .with_prompt("Key visible")
-                                .default(field.key_visible)
-                                .interact()
-                                .unwrap();
-                            field.key_visible = if key_visible { true } else { false };
-                        }
-                        _ => {
-                            break;
-                        }
-                    };
-                }
+                                .interact()?;
+                        field.key = new_key;
+                    }
+                    1 => {
+                        let new_default_value = Input::with_theme(input_theme)
+                            .with_prompt("New default value")
+                            .default(field.default_value.clone())
+                            .interact()?;
+                        field.default_value = new_default_value;
+                    }
+                    2 => {
+                        let current = Alignment::ALL
+                            .iter()
+                            .position(|align| *align == field.align)
+                            .unwrap_or(0);
+                        let align = Select::with_theme(input_theme)
+                            .with_prompt("Align")
+                            .items(Alignment::ALL.map(|align| align.name()))
+                            .default(current)
+                            .interact()?;
+                        field.align = Alignment::ALL[align].clone();
+                    }
+                    3 => {
+                        let key_visible = Confirm::with_theme(input_theme)
+                            .with_prompt("Key visible")
+                            .default(field.key_visible)
+                            .interact()?;
+                        field.key_visible = key_visible;
+                    }
+                    4 => {
+                        let required = Confirm::with_theme(input_theme)
+                            .with_prompt("Required")
+                            .default(field.required)
+                            .interact()?;
+                        field.required = required;
+                    }
+                    5 => {
+                        field.padding_left = Input::with_theme(input_theme)
+                            .with_prompt("Padding left")
+                            .default(field.padding_left)
+                            .interact()?;
+                        field.padding_right = Input::with_theme(input_theme)
+                            .with_prompt("Padding right")
+                            .default(field.padding_right)
+                            .interact()?;
+                    }
+                    6 => kind_select(field, input_theme)?,
+                    7 => {
+                        let group: String = Input::with_theme(input_theme)
+                            .with_prompt("Alignment group (empty for none)")
+                            .default(field.group.clone().unwrap_or_default())
+                            .allow_empty(true)
+                            .interact_text()?;
+                        field.group = Some(group).filter(|group| !group.is_empty());
+                    }
+                    _ => {
+                        break;
+                    }
+                };
             }
         }
-        if let Err(e) = store.save() {
-            println!("{}: {}", style("error"), e);
-        } else {
-            println!("{}", style("Saved").green().bold());
+    }
+    // edit comment style
+    else if comment {
+        let names = CommentStyle::names();
+        let current = names
+            .iter()
+            .position(|name| *name == template.comment_style().name)
+            .unwrap_or(0);
+        let selection = Select::with_theme(input_theme)
+            .with_prompt("Comment style")
+            .items(&names)
+            .default(current)
+            .interact()?;
+        template.comment = Some(names[selection].to_string());
+    }
+    template.validate()?;
+    store.save()?;
+    println!("{}", style("Saved").green().bold());
+    Ok(())
+}
+
+/// Replace the whole template with the one read from `path`
+fn from_spec(header_name: String, path: PathBuf, store: &mut Storage) -> Result<(), Error> {
+    let mut new_template: Template = read_spec(&path)?;
+    new_template.validate()?;
+    if new_template.name != header_name && store.get(&new_template.name).is_some() {
+        return Err(Error::Validation("Header name already exists".to_string()));
+    }
+    let template = store.get_mut(&header_name)?;
+    new_template.default = template.default;
+    *template = new_template;
+    store.save()?;
+    println!("{}", style("Saved").green().bold());
+    Ok(())
+}
+
+/// Insert a rule, blank line or sub-heading between the fields
+fn add_separator(fields: &mut Vec<Field>, input_theme: &ColorfulTheme) -> Result<(), Error> {
+    let kind = Select::with_theme(input_theme)
+        .with_prompt("Separator")
+        .items(FieldKind::SEPARATORS.map(|kind| kind.name()))
+        .default(0)
+        .interact()?;
+    let mut separator = Field::new(FieldKind::SEPARATORS[kind].clone(), String::new());
+    separator_value(&mut separator, input_theme)?;
+
+    let mut positions: Vec<String> = fields
+        .iter()
+        .map(|field| format!("before {}", field.label()))
+        .collect();
+    positions.push("at the end".to_string());
+    let position = Select::with_theme(input_theme)
+        .with_prompt("Position")
+        .items(&positions)
+        .default(fields.len())
+        .interact()?;
+    fields.insert(position, separator);
+    Ok(())
+}
+
+fn edit_separator(
+    fields: &mut Vec<Field>,
+    idx: usize,
+    input_theme: &ColorfulTheme,
+) -> Result<(), Error> {
+    let items = ["value", "align", "padding", "delete"];
+    while let Some(selection) = Select::with_theme(input_theme)
+        .with_prompt("Edit")
+        .items(items)
+        .default(0)
+        .interact_opt()?
+    {
+        let separator = &mut fields[idx];
+        match selection {
+            0 => separator_value(separator, input_theme)?,
+            1 => {
+                let current = Alignment::ALL
+                    .iter()
+                    .position(|align| *align == separator.align)
+                    .unwrap_or(0);
+                let align = Select::with_theme(input_theme)
+                    .with_prompt("Align")
+                    .items(Alignment::ALL.map(|align| align.name()))
+                    .default(current)
+                    .interact()?;
+                separator.align = Alignment::ALL[align].clone();
+            }
+            2 => {
+                separator.padding_left = Input::with_theme(input_theme)
+                    .with_prompt("Padding left")
+                    .default(separator.padding_left)
+                    .interact()?;
+                separator.padding_right = Input::with_theme(input_theme)
+                    .with_prompt("Padding right")
+                    .default(separator.padding_right)
+                    .interact()?;
+            }
+            _ => {
+                fields.remove(idx);
+                break;
+            }
         }
-    } else {
-        println!(
-            "{}",
-            style(format!("`{}` not found", header_name)).red().bold()
-        );
-        return;
     }
+    Ok(())
+}
+
+/// Rule pattern or heading text, blank lines have no value
+fn separator_value(separator: &mut Field, input_theme: &ColorfulTheme) -> Result<(), Error> {
+    let prompt = match separator.kind {
+        FieldKind::Rule => "Rule characters",
+        FieldKind::Heading => "Heading",
+        _ => return Ok(()),
+    };
+    separator.default_value = Input::with_theme(input_theme)
+        .with_prompt(prompt)
+        .default(separator.default_value.clone())
+        .allow_empty(separator.kind == FieldKind::Rule)
+        .interact_text()?;
+    Ok(())
 }
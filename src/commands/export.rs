@@ -0,0 +1,46 @@
+use std::{fs, path::PathBuf};
+
+use console::style;
+
+use crate::{
+    core::{
+        spec::Format,
+        storage::{Library, Storage},
+    },
+    error::{Error, Result},
+};
+
+pub fn run(
+    names: Vec<String>,
+    output: Option<PathBuf>,
+    format: Option<Format>,
+    store: &Storage,
+) -> Result<()> {
+    let templates = if names.is_empty() {
+        store.list().clone()
+    } else {
+        names
+            .iter()
+            .map(|name| {
+                store
+                    .get(name)
+                    .cloned()
+                    .ok_or_else(|| Error::not_found(name))
+            })
+            .collect::<Result<Vec<_>>>()?
+    };
+    let format = format
+        .or_else(|| output.as_deref().and_then(Format::from_path))
+        .unwrap_or(Format::Json);
+    let text = format.write(&Library { templates })?;
+
+    // a library is data, it never goes to the clipboard
+    match output {
+        Some(path) => {
+            fs::write(&path, text).map_err(|e| Error::io(&path, e))?;
+            eprintln!("{} {}", style("Written to").green().bold(), path.display());
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
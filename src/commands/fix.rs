@@ -0,0 +1,67 @@
+use std::{fs, path::PathBuf};
+
+use console::style;
+
+use crate::{
+    commands::FileTemplates,
+    core::{
+        placeholder::Context,
+        source::{HeaderStatus, header_status, insert_header, source_files, update_header},
+        storage::Storage,
+    },
+    error::{Error, Result},
+};
+
+pub fn run(
+    paths: Vec<PathBuf>,
+    name: Option<String>,
+    lang: Option<String>,
+    comment: Option<String>,
+    store: &Storage,
+) -> Result<()> {
+    let mut templates = FileTemplates::new(store, name, lang, comment, true)?;
+
+    // keep going on failures
+    let mut failed = 0;
+    for path in source_files(&paths)? {
+        let Some(template) = templates.get(&path) else {
+            continue;
+        };
+        let context = Context::new(Some(&path));
+        let Ok(text) = fs::read_to_string(&path) else {
+            continue;
+        };
+
+        let (new_text, action) = match header_status(&template, &context, &text) {
+            HeaderStatus::Ok => continue,
+            HeaderStatus::Missing => (
+                insert_header(&template, store.list(), &context, &text),
+                "Inserted",
+            ),
+            HeaderStatus::Stale => (update_header(&template, &context, &text), "Updated"),
+        };
+        let Some(new_text) = new_text else {
+            println!(
+                "{} {} (another header is present)",
+                style("Skipped").yellow().bold(),
+                path.display()
+            );
+            continue;
+        };
+        if let Err(e) = fs::write(&path, new_text) {
+            Error::io(&path, e).report();
+            failed += 1;
+        } else {
+            println!("{} {}", style(action).green().bold(), path.display());
+        }
+    }
+    let failed = failed + templates.failed;
+    if failed > 0 {
+        Err(Error::Io(format!(
+            "{} files could not be processed",
+            failed
+        )))
+    } else {
+        Ok(())
+    }
+}
@@ -1,18 +1,23 @@
-use console::style;
+use crate::{
+    core::{header::get_header, output::Output, storage::Storage},
+    error::{Error, Result},
+};
 
-use crate::core::{header::get_header, storage::Storage};
-
-pub fn run(name: String, json: bool, store: &Storage) {
-    match store.get(&name) {
-        Some(template) => {
-            if json {
-                println!("{}", serde_json::to_string_pretty(template).unwrap());
-            } else {
-                get_header(template);
-            }
-        }
-        None => {
-            println!("{}", style(format!("`{}` not found", name)).red().bold());
-        }
+pub fn run(
+    name: String,
+    json: bool,
+    lang: Option<String>,
+    comment: Option<String>,
+    store: &Storage,
+    output: &Output,
+) -> Result<()> {
+    let template = store.get(&name).ok_or_else(|| Error::not_found(&name))?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(template)?);
+        Ok(())
+    } else {
+        let mut template = template.clone();
+        template.set_comment(lang.as_deref(), comment.as_deref())?;
+        get_header(&template, None, output)
     }
 }
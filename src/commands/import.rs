@@ -0,0 +1,210 @@
+use std::path::PathBuf;
+
+use console::style;
+use serde_json::Value;
+
+use crate::{
+    core::{spec::read_spec, storage::Storage, template::Template},
+    error::{Error, Result},
+};
+
+pub fn run(
+    file: PathBuf,
+    overwrite: bool,
+    rename: bool,
+    skip: bool,
+    store: &mut Storage,
+) -> Result<()> {
+    let items = match read_spec::<Value>(&file)? {
+        Value::Array(items) => items,
+        Value::Object(mut library) => match library.remove("templates") {
+            Some(Value::Array(items)) => items,
+            _ => return Err(Error::Parse("expected a `templates` list".to_string())),
+        },
+        _ => return Err(Error::Parse("expected a list of templates".to_string())),
+    };
+
+    // check every template before touching the store
+    let mut templates: Vec<Template> = vec![];
+    let mut invalid = 0;
+    for (idx, item) in items.into_iter().enumerate() {
+        let name = match item.get("name").and_then(Value::as_str) {
+            Some(name) => name.to_string(),
+            None => format!("#{}", idx + 1),
+        };
+        let template = serde_json::from_value::<Template>(item)
+            .map_err(Error::from)
+            .and_then(|template| template.validate().map(|_| template));
+        match template {
+            Ok(template) if templates.iter().any(|other| other.name == template.name) => {
+                Error::Validation(format!("template `{}`: defined twice", name)).report();
+                invalid += 1;
+            }
+            Ok(template) => templates.push(Template {
+                default: false,
+                ..template
+            }),
+            Err(e) => {
+                Error::Validation(format!("template `{}`: {}", name, e)).report();
+                invalid += 1;
+            }
+        }
+    }
+    if invalid > 0 {
+        return Err(Error::Validation(format!(
+            "{} invalid templates, nothing imported",
+            invalid
+        )));
+    }
+
+    let conflicts: Vec<&str> = templates
+        .iter()
+        .filter(|template| store.get(&template.name).is_some())
+        .map(|template| template.name.as_str())
+        .collect();
+    let resolve = overwrite || rename || skip;
+    if !conflicts.is_empty() && !resolve {
+        return Err(Error::Validation(format!(
+            "already exist: {} (use --overwrite, --rename or --skip)",
+            conflicts.join(", ")
+        )));
+    }
+
+    // project templates can't be replaced, fail before anything is imported
+    if overwrite {
+        let locked: Vec<Error> = conflicts
+            .iter()
+            .filter_map(|name| store.check_user(name).err())
+            .collect();
+        if !locked.is_empty() {
+            let count = locked.len();
+            locked.into_iter().for_each(|e| e.report());
+            return Err(Error::Validation(format!(
+                "{} templates can't be overwritten, nothing imported",
+                count
+            )));
+        }
+    }
+
+    let mut taken: Vec<String> = templates
+        .iter()
+        .map(|template| template.name.clone())
+        .collect();
+    for mut template in templates {
+        if store.get(&template.name).is_none() {
+            eprintln!("{} {}", style("Imported").green().bold(), template.name);
+        } else if overwrite {
+            eprintln!("{} {}", style("Replaced").green().bold(), template.name);
+        } else if rename {
+            let name = (2..)
+                .map(|n| format!("{}-{}", template.name, n))
+                .find(|name| store.get(name).is_none() && !taken.contains(name))
+                .unwrap_or_default();
+            eprintln!(
+                "{} {} as {}",
+                style("Imported").green().bold(),
+                template.name,
+                name
+            );
+            taken.push(name.clone());
+            template.name = name;
+        } else {
+            eprintln!("{} {}", style("Skipped").yellow().bold(), template.name);
+            continue;
+        }
+        store.put(template)?;
+    }
+    store.save()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn named(name: &str, value: &str) -> Value {
+        json!({
+            "name": name,
+            "border": null,
+            "fields": [{ "key": "File", "default_value": value }],
+        })
+    }
+
+    /// Import `[shared, new]` into a store holding the user template `shared`,
+    /// the project template `locked` and, with `locked_too`, a library `locked`
+    fn import(flag: &str, locked_too: bool) -> (Result<()>, Storage) {
+        let dir = env::temp_dir().join(format!("dev-header-import-{}-{}", flag, process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut library = vec![named("shared", "imported"), named("new", "imported")];
+        if locked_too {
+            library.push(named("locked", "imported"));
+        }
+        let file = dir.join("library.json");
+        fs::write(&file, json!({ "templates": library }).to_string()).unwrap();
+
+        let template = |value| serde_json::from_value(named("shared", value)).unwrap();
+        let locked = serde_json::from_value(named("locked", "project")).unwrap();
+        let mut store =
+            Storage::with_templates(vec![template("user")], vec![locked], dir.join("data.json"));
+        let result = run(
+            file,
+            flag == "overwrite",
+            flag == "rename",
+            flag == "skip",
+            &mut store,
+        );
+        fs::remove_dir_all(&dir).unwrap();
+        (result, store)
+    }
+
+    fn value(store: &Storage, name: &str) -> Option<String> {
+        store
+            .get(name)
+            .map(|template| template.fields[0].default_value.clone())
+    }
+
+    #[test]
+    fn conflicts_need_a_flag() {
+        let (result, store) = import("none", false);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "already exist: shared (use --overwrite, --rename or --skip)"
+        );
+        assert_eq!(value(&store, "shared").as_deref(), Some("user"));
+        assert_eq!(value(&store, "new"), None);
+    }
+
+    #[test]
+    fn rename_imports_under_a_free_name() {
+        let (result, store) = import("rename", false);
+        result.unwrap();
+        assert_eq!(value(&store, "shared").as_deref(), Some("user"));
+        assert_eq!(value(&store, "shared-2").as_deref(), Some("imported"));
+        assert_eq!(value(&store, "new").as_deref(), Some("imported"));
+    }
+
+    #[test]
+    fn skip_keeps_existing_templates() {
+        let (result, store) = import("skip", true);
+        result.unwrap();
+        assert_eq!(value(&store, "shared").as_deref(), Some("user"));
+        assert_eq!(value(&store, "locked").as_deref(), Some("project"));
+        assert_eq!(value(&store, "new").as_deref(), Some("imported"));
+    }
+
+    #[test]
+    fn overwrite_replaces_user_templates_only() {
+        let (result, store) = import("overwrite", false);
+        result.unwrap();
+        assert_eq!(value(&store, "shared").as_deref(), Some("imported"));
+
+        // project templates can't be replaced, nothing is imported
+        let (result, store) = import("overwrite", true);
+        assert!(matches!(result, Err(Error::Validation(_))));
+        assert_eq!(value(&store, "shared").as_deref(), Some("user"));
+        assert_eq!(value(&store, "new"), None);
+    }
+}
@@ -0,0 +1,64 @@
+use std::{fs, path::PathBuf};
+
+use console::style;
+
+use crate::{
+    commands::FileTemplates,
+    core::{placeholder::Context, source::insert_header, storage::Storage},
+    error::{Error, Result},
+};
+
+pub fn run(
+    name: Option<String>,
+    rules: bool,
+    paths: Vec<PathBuf>,
+    lang: Option<String>,
+    comment: Option<String>,
+    store: &Storage,
+) -> Result<()> {
+    let (name, paths) = super::name_and_paths(name, rules, paths, store)?;
+    let mut templates = FileTemplates::new(store, name, lang, comment, false)?;
+
+    // keep going on failures
+    let mut failed = 0;
+    for path in paths {
+        let Some(template) = templates.get(&path) else {
+            continue;
+        };
+        let context = Context::new(Some(&path));
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) => {
+                Error::io(&path, e).report();
+                failed += 1;
+                continue;
+            }
+        };
+        match insert_header(&template, store.list(), &context, &text) {
+            Some(new_text) => {
+                if let Err(e) = fs::write(&path, new_text) {
+                    Error::io(&path, e).report();
+                    failed += 1;
+                } else {
+                    println!("{} {}", style("Inserted").green().bold(), path.display());
+                }
+            }
+            None => {
+                println!(
+                    "{} {} (header already present)",
+                    style("Skipped").yellow().bold(),
+                    path.display()
+                );
+            }
+        }
+    }
+    let failed = failed + templates.failed;
+    if failed > 0 {
+        Err(Error::Io(format!(
+            "{} files could not be processed",
+            failed
+        )))
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+use crate::{
+    core::{
+        config::Config,
+        header::get_header,
+        license::{self, License},
+        output::Output,
+        storage::Storage,
+        template::{Field, FieldKind},
+    },
+    error::{Error, Result},
+};
+
+pub fn run(
+    expr: Option<String>,
+    list: bool,
+    name: Option<String>,
+    lang: Option<String>,
+    comment: Option<String>,
+    store: &Storage,
+    output: &Output,
+) -> Result<()> {
+    if list {
+        for license in &License::CATALOGUE {
+            println!("{}", license.id);
+        }
+        return Ok(());
+    }
+    let Some(expr) = expr.or_else(|| Config::current().license.clone()) else {
+        return Err(Error::Validation(
+            "no license given, pass an SPDX expression or set `license` with `header config set`"
+                .to_string(),
+        ));
+    };
+
+    let mut template = match &name {
+        Some(name) => store.get(name).ok_or_else(|| Error::not_found(name))?,
+        None => store
+            .get_default()
+            .ok_or_else(|| Error::NotFound("default header".to_string()))?,
+    }
+    .clone();
+    template.set_comment(lang.as_deref(), comment.as_deref())?;
+
+    // keep the frame of the template, replace its fields with the license
+    let line = |kind: FieldKind, value: String| Field {
+        padding_left: 1,
+        padding_right: 1,
+        ..Field::new(kind, value)
+    };
+    template.fields = vec![line(FieldKind::Text, license::identifier(&expr)?)];
+    let notice = license::notice(&expr)?;
+    if !notice.is_empty() {
+        template.fields.push(line(FieldKind::Blank, String::new()));
+        template.fields.push(line(FieldKind::Multiline, notice));
+    }
+    get_header(&template, None, output)
+}
@@ -1,14 +1,23 @@
 use dialoguer::{Select, theme::ColorfulTheme};
 
-use crate::core::{header::get_header, storage::Storage};
+use crate::{
+    core::{header::get_header, output::Output, spec::require_interactive, storage::Storage},
+    error::Result,
+};
 
-pub fn run(selection: bool, store: &Storage, input_theme: &ColorfulTheme) {
+pub fn run(
+    selection: bool,
+    store: &Storage,
+    output: &Output,
+    input_theme: &ColorfulTheme,
+) -> Result<()> {
     if !selection {
         for template in store.list() {
             println!("{}", template.name);
         }
-        return;
+        return Ok(());
     }
+    require_interactive("use `header get <name>` instead")?;
     let mut names = vec![];
     for template in store.list() {
         names.push(template.name.clone());
@@ -18,10 +27,10 @@ pub fn run(selection: bool, store: &Storage, input_theme: &ColorfulTheme) {
         .with_prompt("Select a header")
         .items(names)
         .default(0)
-        .interact_opt()
-        .unwrap();
+        .interact_opt()?;
 
     if let Some(idx) = selection {
-        get_header(&store.list()[idx]);
+        get_header(&store.list()[idx], None, output)?;
     }
+    Ok(())
 }
@@ -1,12 +1,34 @@
-use clap::{ArgGroup, Parser, Subcommand, command};
+use std::path::{Path, PathBuf};
+
+use clap::{ArgGroup, Args, Parser, Subcommand};
+use console::style;
+
+use crate::{
+    core::{
+        comment::CommentStyle,
+        output::{Output, Sink},
+        spec::{Format, parse_key_value},
+        storage::Storage,
+        template::Template,
+    },
+    error::{Error, Result},
+};
 
 pub mod apply;
+pub mod check;
+pub mod config;
 pub mod default;
 pub mod delete;
 pub mod edit;
+pub mod export;
+pub mod fix;
 pub mod get;
+pub mod import;
+pub mod insert;
+pub mod license;
 pub mod list;
 pub mod new;
+pub mod update;
 
 #[derive(Parser)]
 #[command(
@@ -18,19 +40,171 @@ pub mod new;
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+    // output options given before the command, those given after it win
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+/// Where rendered headers go, for the commands that print one
+#[derive(Args, Debug, Default)]
+pub struct OutputArgs {
+    /// Where to send rendered headers, defaults to the clipboard (stdout if unavailable)
+    #[arg(short, long, value_enum)]
+    pub output: Option<Sink>,
+    /// File to write rendered headers to, implies `--output file`
+    #[arg(long, value_name = "PATH")]
+    pub file: Option<PathBuf>,
+    /// Print rendered headers only, same as `--output stdout`
+    #[arg(long, conflicts_with = "output")]
+    pub no_clipboard: bool,
+}
+
+impl OutputArgs {
+    /// Options of `self`, else of `outer` given before the command, else the sink
+    /// `default` from the config
+    pub fn output(&self, outer: &OutputArgs, default: Option<Sink>) -> Output {
+        let args = if self.output.is_some() || self.file.is_some() || self.no_clipboard {
+            self
+        } else {
+            outer
+        };
+        let sink = if args.no_clipboard {
+            Some(Sink::Stdout)
+        } else if args.output.is_none() && args.file.is_none() {
+            default
+        } else {
+            args.output
+        };
+        Output {
+            sink,
+            file: args.file.clone(),
+        }
+    }
+}
+
+/// Comment style of the rendered headers, else the one of the header or of each file
+#[derive(Args, Debug, Default)]
+pub struct StyleArgs {
+    /// Language to pick the comment style from (rust, python, sql...), overrides the file extension
+    #[arg(short, long, conflicts_with = "comment")]
+    pub lang: Option<String>,
+    /// Comment style (c, doc, hash, dash, semicolon, html, haskell, ocaml, docstring)
+    #[arg(long = "style", value_name = "STYLE")]
+    pub comment: Option<String>,
+}
+
+/// Header name and files of `insert` and `update`: `--name`, or `--rules` to pick the
+/// header of every file from the rules, else the first argument as in
+/// `header insert <name> <paths>...`
+pub fn name_and_paths(
+    name: Option<String>,
+    rules: bool,
+    mut paths: Vec<PathBuf>,
+    store: &Storage,
+) -> Result<(Option<String>, Vec<PathBuf>)> {
+    if name.is_some() || rules {
+        return Ok((name, paths));
+    }
+    let first = paths.remove(0).to_string_lossy().to_string();
+    if store.get(&first).is_none() {
+        if Path::new(&first).exists() {
+            return Err(Error::Validation(format!(
+                "`{}` is a file, give the header name first or use --rules",
+                first
+            )));
+        }
+        return Err(Error::not_found(&first));
+    }
+    if paths.is_empty() {
+        return Err(Error::Validation(format!(
+            "no files given after the header name `{}`",
+            first
+        )));
+    }
+    Ok((Some(first), paths))
+}
+
+/// Template of every file of `insert`, `update`, `check` and `fix`
+pub struct FileTemplates<'a> {
+    store: &'a Storage,
+    name: Option<String>,
+    /// From `--lang`/`--style`, else the style of each file is picked
+    comment: Option<String>,
+    /// Skip files without a known comment syntax instead of keeping the template's style
+    known_only: bool,
+    /// Files the template could not be picked or laid out for
+    pub failed: usize,
+}
+
+impl<'a> FileTemplates<'a> {
+    /// Fails on an unknown header name or comment style, before any file is touched
+    pub fn new(
+        store: &'a Storage,
+        name: Option<String>,
+        lang: Option<String>,
+        comment: Option<String>,
+        known_only: bool,
+    ) -> Result<Self> {
+        if let Some(name) = &name
+            && store.get(name).is_none()
+        {
+            return Err(Error::not_found(name));
+        }
+        Ok(Self {
+            store,
+            name,
+            comment: CommentStyle::resolve(lang.as_deref(), comment.as_deref())?,
+            known_only,
+            failed: 0,
+        })
+    }
+
+    /// Template for `path` with its comment style, `None` for a skipped file or on a
+    /// failure, which is reported and counted
+    pub fn get(&mut self, path: &Path) -> Option<Template> {
+        match self.pick(path) {
+            Ok(template) => template,
+            Err(e) => {
+                eprintln!("{}: {}: {}", style("error").red().bold(), path.display(), e);
+                self.failed += 1;
+                None
+            }
+        }
+    }
+
+    fn pick(&self, path: &Path) -> Result<Option<Template>> {
+        let mut template = self.store.template_for(self.name.as_deref(), path)?.clone();
+        match self
+            .comment
+            .clone()
+            .or_else(|| self.store.comment_for(path))
+        {
+            Some(comment) => template.comment = Some(comment),
+            None if self.known_only => return Ok(None),
+            None => {}
+        }
+        template.check_layout().map_err(Error::Render)?;
+        Ok(Some(template))
+    }
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Create a new header
     #[command(alias = "n")]
-    New,
+    New {
+        /// Read the header from a JSON, TOML or YAML file instead of prompting (`-` for stdin)
+        #[arg(short, long, value_name = "FILE")]
+        from_file: Option<PathBuf>,
+    },
     /// List all headers
     #[command(alias = "l")]
     List {
         /// Selection mode: select a header with default values and copy it to clipboard
         #[arg(short, long)]
         selection: bool,
+        #[command(flatten)]
+        output: OutputArgs,
     },
     /// Get a header
     #[command(alias = "g")]
@@ -40,14 +214,28 @@ pub enum Commands {
         /// JSON view
         #[arg(short, long)]
         json: bool,
+        #[command(flatten)]
+        style: StyleArgs,
+        #[command(flatten)]
+        output: OutputArgs,
     },
     /// Get a header and apply placeholders
     #[command(alias = "a")]
     Apply {
         /// Header name
         name: String,
+        /// Field value, only the other fields are prompted (repeatable)
+        #[arg(short, long = "set", value_name = "KEY=VALUE", value_parser = parse_key_value)]
+        values: Vec<(String, String)>,
+        /// Read field values from a JSON, TOML or YAML map (`-` for stdin)
+        #[arg(short, long, value_name = "FILE")]
+        from_file: Option<PathBuf>,
+        #[command(flatten)]
+        style: StyleArgs,
+        #[command(flatten)]
+        output: OutputArgs,
     },
-    #[command(group(ArgGroup::new("edit_options").required(true).args(&["border", "fields", "name", "spacing"])))]
+    #[command(group(ArgGroup::new("edit_options").required(true).args(&["border", "fields", "name", "spacing", "comment", "separator", "key_width", "from_file"])))]
     /// Edit an existing header
     #[command(alias = "e")]
     Edit {
@@ -59,29 +247,180 @@ pub enum Commands {
         /// Edit header spacing
         #[arg(short, long)]
         spacing: bool,
-        /// Edit header border
-        #[arg(short, long)]
-        border: bool,
+        /// Edit header border, or set a preset (single, double, rounded, heavy)
+        #[arg(short, long, value_name = "PRESET", num_args = 0..=1)]
+        border: Option<Option<String>>,
         /// Edit header fields
         #[arg(short, long)]
         fields: bool,
+        /// Edit header comment style
+        #[arg(short, long)]
+        comment: bool,
+        /// Set the text between a key and its value (default " : ")
+        #[arg(long, value_name = "SEP")]
+        separator: Option<String>,
+        /// Set a fixed key column width, 0 to fit the widest key of each alignment group
+        #[arg(long, value_name = "WIDTH")]
+        key_width: Option<u8>,
+        /// Replace the header with one read from a JSON, TOML or YAML file (`-` for stdin)
+        #[arg(long, value_name = "FILE")]
+        from_file: Option<PathBuf>,
     },
     /// Delete a header
     #[command(alias = "d")]
     Delete {
-        // Delete all headers
+        /// Delete all headers
         #[arg(short, long, conflicts_with = "name")]
         all: bool,
         /// Header name
         #[arg(required_unless_present = "all")]
         name: Option<String>,
+        /// Delete without confirmation
+        #[arg(short, long)]
+        yes: bool,
     },
     /// Get default header and copy it to clipboard
     #[command(alias = "df")]
     Default {
-        /// Placeholder values
+        /// Field values as `KEY=VALUE`, or plain values in field order
         values: Vec<String>,
+        /// Target file, picks the header and comment style from the rules and fills in file placeholders
+        #[arg(short, long, value_name = "FILE")]
+        path: Option<PathBuf>,
+        #[command(flatten)]
+        style: StyleArgs,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Insert a header at the top of files
+    #[command(
+        alias = "i",
+        override_usage = "header insert [OPTIONS] <NAME> <PATHS>...\n       header insert [OPTIONS] --rules <PATHS>..."
+    )]
+    Insert {
+        /// Header name, instead of the first argument
+        #[arg(short, long)]
+        name: Option<String>,
+        /// Pick the header of every file from the rules of the config and project file, then the default header
+        #[arg(long, conflicts_with = "name")]
+        rules: bool,
+        /// Header name, unless given with --name or --rules, then the files to insert the header into
+        #[arg(required = true, value_name = "PATHS")]
+        paths: Vec<PathBuf>,
+        #[command(flatten)]
+        style: StyleArgs,
+    },
+    /// Replace the header at the top of files with a freshly rendered one
+    #[command(
+        alias = "u",
+        override_usage = "header update [OPTIONS] <NAME> <PATHS>...\n       header update [OPTIONS] --rules <PATHS>..."
+    )]
+    Update {
+        /// Header name, instead of the first argument
+        #[arg(short, long)]
+        name: Option<String>,
+        /// Pick the header of every file from the rules of the config and project file, then the default header
+        #[arg(long, conflicts_with = "name")]
+        rules: bool,
+        /// Header name, unless given with --name or --rules, then the files to update
+        #[arg(required = true, value_name = "PATHS")]
+        paths: Vec<PathBuf>,
+        /// Print a unified diff instead of writing the files
+        #[arg(short, long)]
+        dry_run: bool,
+        #[command(flatten)]
+        style: StyleArgs,
+    },
+    /// Report files whose header is missing or stale, exits with 1 if any
+    Check {
+        /// Files or directories, defaults to the current directory
+        paths: Vec<PathBuf>,
+        /// Header name, defaults to the rules of the config and project file, then the default header
+        #[arg(short, long)]
+        name: Option<String>,
+        #[command(flatten)]
+        style: StyleArgs,
+    },
+    /// Insert missing headers and update stale ones
+    Fix {
+        /// Files or directories, defaults to the current directory
+        paths: Vec<PathBuf>,
+        /// Header name, defaults to the rules of the config and project file, then the default header
+        #[arg(short, long)]
+        name: Option<String>,
+        #[command(flatten)]
+        style: StyleArgs,
+    },
+    /// Render an SPDX license notice with a header
+    License {
+        /// SPDX expression, e.g. `MIT OR Apache-2.0`, defaults to `license` from the config
+        expr: Option<String>,
+        /// List the licenses with a built-in notice
+        #[arg(long, conflicts_with = "expr")]
+        list: bool,
+        /// Header to draw the notice with, defaults to the default header
+        #[arg(short, long)]
+        name: Option<String>,
+        #[command(flatten)]
+        style: StyleArgs,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Write headers to a JSON, TOML or YAML library
+    Export {
+        /// Header names, defaults to all headers
+        names: Vec<String>,
+        /// Library file, prints to stdout when not given
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+        /// Library format, defaults to the extension of the output file, then JSON
+        #[arg(long, value_enum)]
+        format: Option<Format>,
+    },
+    #[command(group(ArgGroup::new("conflict").args(&["overwrite", "rename", "skip"])))]
+    /// Add the headers of a JSON, TOML or YAML library, fails on existing names by default
+    Import {
+        /// Library file (`-` for stdin)
+        file: PathBuf,
+        /// Replace headers with the same name
+        #[arg(long)]
+        overwrite: bool,
+        /// Import under a free name, e.g. `name-2`
+        #[arg(long)]
+        rename: bool,
+        /// Keep headers with the same name
+        #[arg(long)]
+        skip: bool,
     },
     /// Manage configuration
-    Config,
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigAction {
+    /// Print a value
+    Get {
+        /// author, email, organisation, license, output, comment or templates.<ext>
+        key: String,
+    },
+    /// Set a value
+    Set {
+        /// author, email, organisation, license, output, comment or templates.<ext>
+        key: String,
+        value: String,
+    },
+    /// Remove a value
+    Unset {
+        /// author, email, organisation, license, output, comment or templates.<ext>
+        key: String,
+    },
+    /// Print all values
+    List,
+    /// Print the path of the config file
+    Path,
+    /// Open the config file in $VISUAL or $EDITOR
+    Edit,
 }
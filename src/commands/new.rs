@@ -1,24 +1,39 @@
-use console::style;
-use dialoguer::{Confirm, Input, theme::ColorfulTheme};
+use std::path::PathBuf;
 
-use crate::core::{
-    header::border_input,
-    storage::Storage,
-    template::{Alignment, Border, Field, Template},
+use dialoguer::{Confirm, Input, Select, theme::ColorfulTheme};
+
+use crate::{
+    core::{
+        comment::CommentStyle,
+        header::{border_select, kind_select},
+        spec::{read_spec, require_interactive},
+        storage::Storage,
+        template::{Alignment, Border, Field, FieldKind, Template},
+        text,
+    },
+    error::Error,
 };
 
-pub fn run(store: &mut Storage, input_theme: &ColorfulTheme) {
+pub fn run(
+    from_file: Option<PathBuf>,
+    store: &mut Storage,
+    input_theme: &ColorfulTheme,
+) -> Result<(), Error> {
+    if let Some(path) = from_file {
+        return from_spec(path, store);
+    }
+    require_interactive("use --from-file to create a header")?;
+
     let name: String = Input::with_theme(input_theme)
         .with_prompt("Header name")
         .validate_with(|input: &String| -> Result<(), &str> {
-            if let None = store.get(&input) {
+            if store.get(input).is_none() {
                 Ok(())
             } else {
                 Err("Header name already exists")
             }
         })
-        .interact()
-        .unwrap();
+        .interact()?;
 
     let length: u16 = Input::with_theme(input_theme)
         .with_prompt("Header length")
@@ -33,71 +48,55 @@ pub fn run(store: &mut Storage, input_theme: &ColorfulTheme) {
                 ))
             }
         })
-        .interact()
-        .unwrap();
+        .interact()?;
 
     let space_before: u8 = Input::with_theme(input_theme)
                 .with_prompt("Space before")
                 .default(Template::SPACE_HEIGHT)
                 .validate_with(|input: &u8| -> Result<(), String> {
-                    if *input <= Template::SPACE_HEIGHT {
+                    if *input <= Template::MAX_SPACE_HEIGHT {
                         Ok(())
                     } else {
                         Err(format!(
                             "Space before paragraph must greater than or equal to 0 and less than or equal to {}",
-                            Template::SPACE_HEIGHT
+                            Template::MAX_SPACE_HEIGHT
                         ))
                     }
                 })
-                .interact()
-                .unwrap();
+                .interact()?;
 
     let space_after: u8 = Input::with_theme(input_theme)
-                .with_prompt("Space before")
+                .with_prompt("Space after")
                 .default(Template::SPACE_HEIGHT)
                 .validate_with(|input: &u8| -> Result<(), String> {
-                    if *input <= Template::SPACE_HEIGHT {
+                    if *input <= Template::MAX_SPACE_HEIGHT {
                         Ok(())
                     } else {
                         Err(format!(
-                            "Space before paragraph must greater than or equal to 0 and less than or equal to {}",
-                            Template::SPACE_HEIGHT
+                            "Space after paragraph must greater than or equal to 0 and less than or equal to {}",
+                            Template::MAX_SPACE_HEIGHT
                         ))
                     }
                 })
-                .interact()
-                .unwrap();
+                .interact()?;
 
     let mut border: Option<Border> = None;
     if Confirm::with_theme(input_theme)
         .with_prompt("Add border?")
-        .interact()
-        .unwrap()
+        .interact()?
     {
-        let left = border_input("Left", input_theme);
-        let top = border_input("Top", input_theme);
-        let right = border_input("Right", input_theme);
-        let bottom = border_input("Bottom", input_theme);
-        if left.is_some() || top.is_some() || right.is_some() || left.is_some() {
-            border = Some(Border {
-                left,
-                top,
-                right,
-                bottom,
-            });
-        }
+        border = border_select(input_theme)?;
     }
 
     let mut fields = vec![];
     while Confirm::with_theme(input_theme)
         .with_prompt("Add field")
-        .interact()
-        .unwrap()
+        .interact()?
     {
         let key: String = Input::with_theme(input_theme)
             .with_prompt("Key")
             .validate_with(|input: &String| -> Result<(), String> {
-                if input.len() > (length / 2) as usize {
+                if text::width(input) > (length / 2) as usize {
                     Err(
                         "The length of the key must be less than half of the total length"
                             .to_string(),
@@ -106,52 +105,70 @@ pub fn run(store: &mut Storage, input_theme: &ColorfulTheme) {
                     Ok(())
                 }
             })
-            .interact()
-            .unwrap();
+            .interact()?;
         let value: String = Input::with_theme(input_theme)
             .with_prompt("Value")
-            .interact()
-            .unwrap();
+            .interact()?;
         let key_visible = Confirm::with_theme(input_theme)
             .with_prompt("Key visible")
-            .interact()
-            .unwrap();
-        let center = Confirm::with_theme(input_theme)
-            .with_prompt("Text center")
-            .interact()
-            .unwrap();
-        fields.push(Field {
-            align: if center {
-                Alignment::Center
-            } else {
-                Alignment::Left
-            },
-            key: key,
-            default_value: value,
-            key_visible: key_visible,
-        });
+            .interact()?;
+        let align = Select::with_theme(input_theme)
+            .with_prompt("Align")
+            .items(Alignment::ALL.map(|align| align.name()))
+            .default(0)
+            .interact()?;
+        let required = Confirm::with_theme(input_theme)
+            .with_prompt("Required")
+            .interact()?;
+        let mut field = Field {
+            align: Alignment::ALL[align].clone(),
+            key,
+            key_visible,
+            required,
+            ..Field::new(FieldKind::Text, value)
+        };
+        kind_select(&mut field, input_theme)?;
+        fields.push(field);
     }
 
-    if border.is_none() && fields.len() == 0 {
+    let names = CommentStyle::names();
+    let comment = Select::with_theme(input_theme)
+        .with_prompt("Comment style")
+        .items(&names)
+        .default(0)
+        .interact()?;
+
+    if border.is_none() && fields.is_empty() {
         border = Some(Border {
-            left: None,
-            top: Some('#'),
-            right: None,
-            bottom: Some('#'),
+            top: Some("#".to_string()),
+            bottom: Some("#".to_string()),
+            ..Border::default()
         });
     }
 
     let template = Template {
-        border: border,
+        border,
         default: false,
-        fields: fields,
-        length: length,
-        name: name,
-        space_before: space_before,
-        space_after: space_after,
+        fields,
+        length,
+        name,
+        space_before,
+        space_after,
+        comment: Some(names[comment].to_string()),
+        separator: Template::DEFAULT_SEPARATOR.to_string(),
+        key_width: None,
     };
+    template.validate()?;
+
+    store.add(template)
+}
 
-    if let Err(e) = store.add(template) {
-        println!("{}: {}", style("error").red().bold(), e)
+fn from_spec(path: PathBuf, store: &mut Storage) -> Result<(), Error> {
+    let mut template: Template = read_spec(&path)?;
+    template.default = false;
+    template.validate()?;
+    if store.get(&template.name).is_some() {
+        return Err(Error::Validation("Header name already exists".to_string()));
     }
+    store.add(template)
 }
@@ -0,0 +1,76 @@
+use std::{fs, path::PathBuf};
+
+use console::style;
+use similar::TextDiff;
+
+use crate::{
+    commands::FileTemplates,
+    core::{placeholder::Context, source::update_header, storage::Storage},
+    error::{Error, Result},
+};
+
+pub fn run(
+    name: Option<String>,
+    rules: bool,
+    paths: Vec<PathBuf>,
+    dry_run: bool,
+    lang: Option<String>,
+    comment: Option<String>,
+    store: &Storage,
+) -> Result<()> {
+    let (name, paths) = super::name_and_paths(name, rules, paths, store)?;
+    let mut templates = FileTemplates::new(store, name, lang, comment, false)?;
+
+    // keep going on failures
+    let mut failed = 0;
+    for path in paths {
+        let Some(template) = templates.get(&path) else {
+            continue;
+        };
+        let context = Context::new(Some(&path));
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) => {
+                Error::io(&path, e).report();
+                failed += 1;
+                continue;
+            }
+        };
+        let Some(new_text) = update_header(&template, &context, &text) else {
+            println!(
+                "{} {} (no header found)",
+                style("Skipped").yellow().bold(),
+                path.display()
+            );
+            continue;
+        };
+        if new_text == text {
+            println!("{} {}", style("Up to date").green().bold(), path.display());
+            continue;
+        }
+
+        if dry_run {
+            let name = path.display().to_string();
+            print!(
+                "{}",
+                TextDiff::from_lines(&text, &new_text)
+                    .unified_diff()
+                    .header(&name, &name)
+            );
+        } else if let Err(e) = fs::write(&path, new_text) {
+            Error::io(&path, e).report();
+            failed += 1;
+        } else {
+            println!("{} {}", style("Updated").green().bold(), path.display());
+        }
+    }
+    let failed = failed + templates.failed;
+    if failed > 0 {
+        Err(Error::Io(format!(
+            "{} files could not be processed",
+            failed
+        )))
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,203 @@
+use std::path::Path;
+
+use crate::{
+    core::config::Config,
+    error::{Error, Result},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommentStyle {
+    pub name: &'static str,
+    /// Prefix for single line comments (`//`, `#`, `--`...)
+    pub line: Option<&'static str>,
+    /// Block comment delimiters (`/*`, `*/`)
+    pub open: Option<&'static str>,
+    pub close: Option<&'static str>,
+    /// Prefix for every line inside a block comment (` *`)
+    pub continuation: Option<&'static str>,
+}
+
+impl CommentStyle {
+    pub const BUILTIN: &'static [CommentStyle] = &[
+        CommentStyle {
+            name: "c",
+            line: Some("//"),
+            open: Some("/*"),
+            close: Some("*/"),
+            continuation: None,
+        },
+        CommentStyle {
+            name: "doc",
+            line: None,
+            open: Some("/**"),
+            close: Some(" */"),
+            continuation: Some(" *"),
+        },
+        CommentStyle {
+            name: "hash",
+            line: Some("#"),
+            open: None,
+            close: None,
+            continuation: None,
+        },
+        CommentStyle {
+            name: "dash",
+            line: Some("--"),
+            open: None,
+            close: None,
+            continuation: None,
+        },
+        CommentStyle {
+            name: "semicolon",
+            line: Some(";"),
+            open: None,
+            close: None,
+            continuation: None,
+        },
+        CommentStyle {
+            name: "html",
+            line: None,
+            open: Some("<!--"),
+            close: Some("-->"),
+            continuation: None,
+        },
+        CommentStyle {
+            name: "haskell",
+            line: Some("--"),
+            open: Some("{-"),
+            close: Some("-}"),
+            continuation: None,
+        },
+        CommentStyle {
+            name: "ocaml",
+            line: None,
+            open: Some("(*"),
+            close: Some("*)"),
+            continuation: None,
+        },
+        CommentStyle {
+            name: "docstring",
+            line: None,
+            open: Some("\"\"\""),
+            close: Some("\"\"\""),
+            continuation: None,
+        },
+    ];
+
+    /// Languages (and file extensions) mapped to a built-in style
+    const LANGUAGES: &'static [(&'static str, &'static str)] = &[
+        ("c", "c"),
+        ("h", "c"),
+        ("cpp", "c"),
+        ("hpp", "c"),
+        ("cs", "c"),
+        ("rust", "c"),
+        ("rs", "c"),
+        ("go", "c"),
+        ("java", "c"),
+        ("kotlin", "c"),
+        ("kt", "c"),
+        ("javascript", "c"),
+        ("js", "c"),
+        ("typescript", "c"),
+        ("ts", "c"),
+        ("swift", "c"),
+        ("php", "c"),
+        ("css", "c"),
+        ("scss", "c"),
+        ("python", "hash"),
+        ("py", "hash"),
+        ("shell", "hash"),
+        ("sh", "hash"),
+        ("bash", "hash"),
+        ("zsh", "hash"),
+        ("ruby", "hash"),
+        ("rb", "hash"),
+        ("perl", "hash"),
+        ("pl", "hash"),
+        ("r", "hash"),
+        ("yaml", "hash"),
+        ("yml", "hash"),
+        ("toml", "hash"),
+        ("makefile", "hash"),
+        ("dockerfile", "hash"),
+        ("sql", "dash"),
+        ("lua", "dash"),
+        ("ada", "dash"),
+        ("lisp", "semicolon"),
+        ("clojure", "semicolon"),
+        ("clj", "semicolon"),
+        ("scheme", "semicolon"),
+        ("asm", "semicolon"),
+        ("ini", "semicolon"),
+        ("html", "html"),
+        ("htm", "html"),
+        ("xml", "html"),
+        ("svg", "html"),
+        ("markdown", "html"),
+        ("md", "html"),
+        ("vue", "html"),
+        ("haskell", "haskell"),
+        ("hs", "haskell"),
+        ("elm", "haskell"),
+        ("ocaml", "ocaml"),
+        ("ml", "ocaml"),
+        ("fsharp", "ocaml"),
+        ("fs", "ocaml"),
+        ("pascal", "ocaml"),
+    ];
+
+    pub fn get(name: &str) -> Option<&'static CommentStyle> {
+        Self::BUILTIN.iter().find(|style| style.name == name)
+    }
+
+    pub fn from_lang(lang: &str) -> Option<&'static CommentStyle> {
+        let lang = lang.to_lowercase();
+        Self::LANGUAGES
+            .iter()
+            .find(|(name, _)| *name == lang)
+            .and_then(|(_, style)| Self::get(style))
+    }
+
+    /// Pick a style from the file extension, or the file name (`Makefile`, `Dockerfile`)
+    pub fn from_path(path: &Path) -> Option<&'static CommentStyle> {
+        path.extension()
+            .or_else(|| path.file_name())
+            .and_then(|name| name.to_str())
+            .and_then(Self::from_lang)
+    }
+
+    /// `comment` from the config, otherwise `c`
+    pub fn default_style() -> &'static CommentStyle {
+        Config::current()
+            .comment
+            .as_deref()
+            .and_then(Self::get)
+            .unwrap_or(&Self::BUILTIN[0])
+    }
+
+    pub fn names() -> Vec<&'static str> {
+        Self::BUILTIN.iter().map(|style| style.name).collect()
+    }
+
+    /// Resolve a `--lang`/`--style` pair into a style name
+    pub fn resolve(lang: Option<&str>, style: Option<&str>) -> Result<Option<String>> {
+        if let Some(style) = style {
+            return match Self::get(style) {
+                Some(style) => Ok(Some(style.name.to_string())),
+                None => Err(Error::Validation(format!(
+                    "unknown comment style `{}` (available: {})",
+                    style,
+                    Self::names().join(", ")
+                ))),
+            };
+        }
+        if let Some(lang) = lang {
+            return match Self::from_lang(lang) {
+                Some(style) => Ok(Some(style.name.to_string())),
+                None => Err(Error::Validation(format!("unknown language `{}`", lang))),
+            };
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,274 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::{comment::CommentStyle, license, output::Sink, rule::PathRule, storage},
+    error::{Error, Result},
+};
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// User settings, stored in `config.toml` next to `data.json`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Config {
+    /// Used for `{{author}}` before git and the user name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Used for `{{email}}` before git
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// Used for `{{organisation}}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organisation: Option<String>,
+    /// SPDX expression for `{{license}}` and `header license`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// Sink when no `--output` is given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<Sink>,
+    /// Comment style of templates without one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// File extension to template name, when no rule matches
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, String>,
+    /// Path globs to template and comment style, after the rules of the project file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<PathRule>,
+}
+
+impl Config {
+    pub const KEYS: [&str; 7] = [
+        "author",
+        "email",
+        "organisation",
+        "license",
+        "output",
+        "comment",
+        "templates.<ext>",
+    ];
+
+    pub fn path() -> Result<PathBuf> {
+        Ok(storage::data_dir()?.join("config.toml"))
+    }
+
+    /// Read the config file, a missing file is an empty config
+    pub fn read() -> Result<Self> {
+        let path = Self::path()?;
+        match fs::read_to_string(&path) {
+            Ok(text) => toml::from_str(&text)
+                .map_err(|e| Error::Parse(format!("{}: {}", path.display(), e))),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(Error::io(&path, e)),
+        }
+    }
+
+    /// Read the config file once, later calls return the same config
+    pub fn load() -> Result<&'static Self> {
+        if let Some(config) = CONFIG.get() {
+            return Ok(config);
+        }
+        let config = Self::read()?;
+        Ok(CONFIG.get_or_init(|| config))
+    }
+
+    /// Loaded config, empty when `load` was not called or failed
+    pub fn current() -> &'static Self {
+        CONFIG.get_or_init(Self::default)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        let text = toml::to_string(self).map_err(|e| Error::Parse(e.to_string()))?;
+        fs::write(&path, text).map_err(|e| Error::io(&path, e))
+    }
+
+    pub fn get(&self, key: &str) -> Result<Option<String>> {
+        let value = match key {
+            "author" => self.author.clone(),
+            "email" => self.email.clone(),
+            "organisation" => self.organisation.clone(),
+            "license" => self.license.clone(),
+            "output" => self.output.map(sink_name),
+            "comment" => self.comment.clone(),
+            _ => self.templates.get(extension(key)?).cloned(),
+        };
+        Ok(value)
+    }
+
+    /// Set `key`, template names are checked by the caller
+    pub fn set(&mut self, key: &str, value: String) -> Result<()> {
+        match key {
+            "author" => self.author = Some(value),
+            "email" => self.email = Some(value),
+            "organisation" => self.organisation = Some(value),
+            "license" => {
+                license::validate(&value)?;
+                self.license = Some(value);
+            }
+            "output" => {
+                let sink = Sink::from_str(&value, false).map_err(|_| {
+                    Error::Validation(format!(
+                        "unknown output `{}` (available: {})",
+                        value,
+                        Sink::value_variants()
+                            .iter()
+                            .map(|sink| sink_name(*sink))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ))
+                })?;
+                self.output = Some(sink);
+            }
+            "comment" => {
+                if CommentStyle::get(&value).is_none() {
+                    return Err(Error::Validation(format!(
+                        "unknown comment style `{}` (available: {})",
+                        value,
+                        CommentStyle::names().join(", ")
+                    )));
+                }
+                self.comment = Some(value);
+            }
+            _ => {
+                let ext = extension(key)?.to_string();
+                self.templates.insert(ext, value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove `key`, returns whether it was set
+    pub fn unset(&mut self, key: &str) -> Result<bool> {
+        let was_set = match key {
+            "author" => self.author.take().is_some(),
+            "email" => self.email.take().is_some(),
+            "organisation" => self.organisation.take().is_some(),
+            "license" => self.license.take().is_some(),
+            "output" => self.output.take().is_some(),
+            "comment" => self.comment.take().is_some(),
+            _ => self.templates.remove(extension(key)?).is_some(),
+        };
+        Ok(was_set)
+    }
+
+    /// Every set key with its value, rules are only changed with `config edit`
+    pub fn list(&self) -> Vec<(String, String)> {
+        let mut values = vec![];
+        for key in &Self::KEYS[..Self::KEYS.len() - 1] {
+            if let Ok(Some(value)) = self.get(key) {
+                values.push((key.to_string(), value));
+            }
+        }
+        for (ext, name) in &self.templates {
+            values.push((format!("templates.{}", ext), name.clone()));
+        }
+        values
+    }
+
+    /// Template mapped to the extension of `path`
+    pub fn template_for(&self, path: &Path) -> Option<&str> {
+        let ext = path.extension()?.to_str()?;
+        self.templates.get(ext).map(String::as_str)
+    }
+}
+
+/// `rs` out of `templates.rs`
+fn extension(key: &str) -> Result<&str> {
+    match key.strip_prefix("templates.") {
+        Some(ext) if !ext.is_empty() => Ok(ext.trim_start_matches('.')),
+        _ => Err(Error::Validation(format!(
+            "unknown config key `{}` (available: {})",
+            key,
+            Config::KEYS.join(", ")
+        ))),
+    }
+}
+
+fn sink_name(sink: Sink) -> String {
+    sink.to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_get_and_unset() {
+        let mut config = Config::default();
+        config.set("author", "Jane".to_string()).unwrap();
+        config.set("output", "stdout".to_string()).unwrap();
+        config
+            .set("templates..rs", "rust_header".to_string())
+            .unwrap();
+        assert_eq!(config.get("author").unwrap().as_deref(), Some("Jane"));
+        assert_eq!(config.get("output").unwrap().as_deref(), Some("stdout"));
+        assert_eq!(
+            config.template_for(Path::new("src/main.rs")),
+            Some("rust_header")
+        );
+
+        assert!(config.unset("author").unwrap());
+        assert!(!config.unset("author").unwrap());
+        assert!(config.unset("templates.rs").unwrap());
+        assert_eq!(config.get("templates.rs").unwrap(), None);
+    }
+
+    #[test]
+    fn set_rejects_invalid_values_and_keys() {
+        let mut config = Config::default();
+        for (key, value) in [
+            ("output", "printer"),
+            ("comment", "nope"),
+            ("license", "Not-A-License"),
+            ("rules", "x"),
+            ("templates.", "x"),
+        ] {
+            assert!(
+                matches!(
+                    config.set(key, value.to_string()),
+                    Err(Error::Validation(_))
+                ),
+                "{} = {}",
+                key,
+                value
+            );
+        }
+        assert!(config.unset("colour").is_err());
+    }
+
+    #[test]
+    fn list_leaves_out_rules() {
+        let mut config = Config::default();
+        config.set("license", "MIT".to_string()).unwrap();
+        config.set("comment", "hash".to_string()).unwrap();
+        config.set("templates.py", "py_header".to_string()).unwrap();
+        config.rules.push(PathRule {
+            glob: "*.rs".to_string(),
+            template: None,
+            comment: None,
+        });
+        let list = config.list();
+        let list: Vec<(&str, &str)> = list
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect();
+        assert_eq!(
+            list,
+            [
+                ("license", "MIT"),
+                ("comment", "hash"),
+                ("templates.py", "py_header")
+            ]
+        );
+    }
+}
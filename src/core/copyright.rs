@@ -0,0 +1,185 @@
+use std::fmt;
+
+/// Years of a copyright notice, e.g. `2018, 2020-2024`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Years {
+    /// Sorted inclusive ranges, neither overlapping nor adjacent
+    ranges: Vec<(i32, i32)>,
+}
+
+impl Years {
+    /// Year list at the start of `text`, after an optional `Copyright (c)`,
+    /// and the text that follows it
+    pub fn split(text: &str) -> (Years, &str) {
+        let mut rest = strip_prefix_ignore_case(text.trim_start(), "copyright").trim_start();
+        for mark in ["(c)", "(C)", "©"] {
+            if let Some(stripped) = rest.strip_prefix(mark) {
+                rest = stripped.trim_start();
+                break;
+            }
+        }
+
+        let mut years = Years::default();
+        while let Some((start, after)) = year(rest) {
+            let (end, after) = match after.trim_start().strip_prefix(['-', '–']) {
+                Some(range) => year(range.trim_start()).unwrap_or((start, after)),
+                None => (start, after),
+            };
+            years.ranges.push((start.min(end), start.max(end)));
+            rest = after;
+            match rest.trim_start().strip_prefix(',') {
+                Some(next) if year(next.trim_start()).is_some() => rest = next.trim_start(),
+                _ => break,
+            }
+        }
+        years.normalize();
+        (years, rest.trim_start())
+    }
+
+    /// Years of the `nth` copyright notice in `header`
+    pub fn find(header: &str, nth: usize) -> Years {
+        header
+            .lines()
+            .filter_map(|line| {
+                let lower = line.to_ascii_lowercase();
+                // a visible key may come before the notice
+                lower
+                    .match_indices("copyright")
+                    .map(|(start, _)| Years::split(&line[start..]).0)
+                    .find(|years| !years.is_empty())
+            })
+            .nth(nth)
+            .unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn merge(&mut self, other: &Years) {
+        self.ranges.extend(&other.ranges);
+        self.normalize();
+    }
+
+    /// Extend the last range up to `year`, or add it when it is older
+    pub fn extend_to(&mut self, year: i32) {
+        match self.ranges.last_mut() {
+            Some(last) if last.1 < year => last.1 = year,
+            _ => self.ranges.push((year, year)),
+        }
+        self.normalize();
+    }
+
+    /// Sort and collapse duplicate, overlapping and adjacent ranges
+    fn normalize(&mut self) {
+        self.ranges.sort();
+        let mut ranges: Vec<(i32, i32)> = vec![];
+        for (start, end) in self.ranges.drain(..) {
+            match ranges.last_mut() {
+                Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+                _ => ranges.push((start, end)),
+            }
+        }
+        self.ranges = ranges;
+    }
+}
+
+impl fmt::Display for Years {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ranges: Vec<String> = self
+            .ranges
+            .iter()
+            .map(|(start, end)| {
+                if start == end {
+                    start.to_string()
+                } else {
+                    format!("{}-{}", start, end)
+                }
+            })
+            .collect();
+        write!(f, "{}", ranges.join(", "))
+    }
+}
+
+/// `Copyright (c) <years> <holder>`
+pub fn notice(years: &Years, holder: &str) -> String {
+    format!("Copyright (c) {} {}", years, holder)
+        .trim_end()
+        .to_string()
+}
+
+/// Four digit year at the start of `text`
+fn year(text: &str) -> Option<(i32, &str)> {
+    let digits = text.bytes().take_while(u8::is_ascii_digit).count();
+    if digits != 4 {
+        return None;
+    }
+    Some((text[..4].parse().ok()?, &text[4..]))
+}
+
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> &'a str {
+    match text.get(..prefix.len()) {
+        Some(start) if start.eq_ignore_ascii_case(prefix) => &text[prefix.len()..],
+        _ => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_reads_ranges_and_lists() {
+        let (years, rest) = Years::split("2019-2021, 2023 Acme");
+        assert_eq!(years.to_string(), "2019-2021, 2023");
+        assert_eq!(rest, "Acme");
+
+        let (years, rest) = Years::split("Copyright (c) 2018, 2020–2024 Acme, Inc");
+        assert_eq!(years.to_string(), "2018, 2020-2024");
+        assert_eq!(rest, "Acme, Inc");
+    }
+
+    #[test]
+    fn split_collapses_duplicates() {
+        let (years, _) = Years::split("2021, 2019-2020, 2020, 2022");
+        assert_eq!(years.to_string(), "2019-2022");
+    }
+
+    #[test]
+    fn split_without_years() {
+        let (years, rest) = Years::split("Acme 2020");
+        assert!(years.is_empty());
+        assert_eq!(rest, "Acme 2020");
+        // not a year
+        let (years, rest) = Years::split("20245 Acme");
+        assert!(years.is_empty());
+        assert_eq!(rest, "20245 Acme");
+    }
+
+    #[test]
+    fn find_skips_keys_and_other_lines() {
+        let header = "/*\n * File : a.rs\n * Copyright : Copyright (c) 2019-2021, 2023 Acme\n \
+                      * copyright 2010 Other\n */";
+        assert_eq!(Years::find(header, 0).to_string(), "2019-2021, 2023");
+        assert_eq!(Years::find(header, 1).to_string(), "2010");
+        assert!(Years::find(header, 2).is_empty());
+    }
+
+    #[test]
+    fn merge_and_extend() {
+        let (mut years, _) = Years::split("2019-2021, 2023");
+        years.merge(&Years::split("2022").0);
+        assert_eq!(years.to_string(), "2019-2023");
+
+        years.extend_to(2026);
+        assert_eq!(years.to_string(), "2019-2026");
+        // older years are added on their own
+        years.extend_to(2010);
+        assert_eq!(years.to_string(), "2010, 2019-2026");
+
+        let mut years = Years::default();
+        years.extend_to(2025);
+        assert_eq!(years.to_string(), "2025");
+        assert_eq!(notice(&years, "Acme"), "Copyright (c) 2025 Acme");
+    }
+}
@@ -0,0 +1,266 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+};
+
+use chrono::{DateTime, FixedOffset};
+use console::style;
+use git2::{Delta, Repository, Sort, Time};
+
+/// Commits touching a file, newest first
+#[derive(Debug, Clone)]
+pub struct FileHistory {
+    pub commits: Vec<Commit>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Commit {
+    pub author: String,
+    pub date: DateTime<FixedOffset>,
+}
+
+impl FileHistory {
+    pub fn created(&self) -> Option<&Commit> {
+        self.commits.last()
+    }
+
+    pub fn modified(&self) -> Option<&Commit> {
+        self.commits.first()
+    }
+
+    /// Authors in order of their first commit
+    pub fn contributors(&self) -> Vec<&str> {
+        let mut authors: Vec<&str> = vec![];
+        for commit in self.commits.iter().rev() {
+            if !authors.contains(&commit.author.as_str()) {
+                authors.push(&commit.author);
+            }
+        }
+        authors
+    }
+}
+
+/// A repository read from its `.git` directory, opened once per run
+struct Repo {
+    repo: Repository,
+    /// Commits of every file by its path from the root, read on first use
+    history: Option<HashMap<PathBuf, Vec<Commit>>>,
+    config: HashMap<String, Option<String>>,
+}
+
+fn parent_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+/// Closest directory holding `.git` above `path`, `None` outside a repository
+fn repo_root(path: &Path) -> Option<PathBuf> {
+    let dir = parent_dir(path).canonicalize().ok()?;
+    dir.ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Run `f` with the repository containing `path`. A repository that can't be read
+/// is reported once.
+fn with_repo<T>(path: &Path, f: impl FnOnce(&mut Repo) -> Option<T>) -> Option<T> {
+    static REPOS: OnceLock<Mutex<HashMap<PathBuf, Option<Repo>>>> = OnceLock::new();
+    let root = repo_root(path)?;
+    let mut repos = REPOS.get_or_init(Default::default).lock().ok()?;
+    let repo = repos
+        .entry(root.clone())
+        .or_insert_with(|| match Repository::open(&root) {
+            Ok(repo) => Some(Repo {
+                repo,
+                history: None,
+                config: HashMap::new(),
+            }),
+            Err(e) => {
+                eprintln!(
+                    "{}: could not read the git repository {}: {}",
+                    style("warning").yellow().bold(),
+                    root.display(),
+                    e.message()
+                );
+                None
+            }
+        });
+    f(repo.as_mut()?)
+}
+
+/// Git config value of the repository containing `path`, else of the user, cached
+/// for the whole run
+pub fn config(path: &Path, key: &str) -> Option<String> {
+    static USER: OnceLock<Mutex<HashMap<String, Option<String>>>> = OnceLock::new();
+    if repo_root(path).is_some() {
+        return with_repo(path, |repo| {
+            let config = repo.repo.config().ok();
+            repo.config
+                .entry(key.to_string())
+                .or_insert_with(|| config?.get_string(key).ok())
+                .clone()
+        });
+    }
+    let mut cache = USER.get_or_init(Default::default).lock().ok()?;
+    cache
+        .entry(key.to_string())
+        .or_insert_with(|| git2::Config::open_default().ok()?.get_string(key).ok())
+        .clone()
+}
+
+pub fn file_history(path: &Path) -> Option<FileHistory> {
+    let root = repo_root(path)?;
+    let dir = parent_dir(path).canonicalize().ok()?;
+    let relative = dir.strip_prefix(&root).ok()?.join(path.file_name()?);
+    with_repo(path, |repo| {
+        let history = repo.history.get_or_insert_with(|| read_history(&repo.repo));
+        Some(FileHistory {
+            commits: history.get(&relative).cloned().unwrap_or_default(),
+        })
+    })
+}
+
+/// Commits of every file from HEAD back, newest first. Files keep the commits made
+/// under their former names, merges are left out like their changes are counted in
+/// the merged commits.
+fn read_history(repo: &Repository) -> HashMap<PathBuf, Vec<Commit>> {
+    let mut history: HashMap<PathBuf, Vec<Commit>> = HashMap::new();
+    let Ok(mut walk) = repo.revwalk() else {
+        return history;
+    };
+    // an empty repository has no HEAD
+    if walk.push_head().is_err() || walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME).is_err() {
+        return history;
+    }
+
+    // path of a file in older commits, to its path at HEAD
+    let mut renamed: HashMap<PathBuf, PathBuf> = HashMap::new();
+    for commit in walk.flatten().filter_map(|oid| repo.find_commit(oid).ok()) {
+        if commit.parent_count() > 1 {
+            continue;
+        }
+        let parent = commit.parent(0).ok().and_then(|parent| parent.tree().ok());
+        let Ok(tree) = commit.tree() else {
+            continue;
+        };
+        let Ok(mut diff) = repo.diff_tree_to_tree(parent.as_ref(), Some(&tree), None) else {
+            continue;
+        };
+        // without it, renames are seen as a deleted and an added file
+        if diff.find_similar(None).is_err() {
+            continue;
+        }
+        let Some(date) = commit_date(commit.author().when()) else {
+            continue;
+        };
+        let author = String::from_utf8_lossy(commit.author().name_bytes()).to_string();
+
+        for delta in diff.deltas() {
+            let Some(path) = delta.new_file().path() else {
+                continue;
+            };
+            if delta.status() == Delta::Deleted {
+                continue;
+            }
+            let current = match delta.status() {
+                Delta::Renamed => renamed.remove(path),
+                _ => renamed.get(path).cloned(),
+            }
+            .unwrap_or_else(|| path.to_path_buf());
+            if delta.status() == Delta::Renamed
+                && let Some(old) = delta.old_file().path()
+            {
+                renamed.insert(old.to_path_buf(), current.clone());
+            }
+            history.entry(current).or_default().push(Commit {
+                author: author.clone(),
+                date,
+            });
+        }
+    }
+    history
+}
+
+fn commit_date(time: Time) -> Option<DateTime<FixedOffset>> {
+    let offset = FixedOffset::east_opt(time.offset_minutes() * 60)?;
+    Some(DateTime::from_timestamp(time.seconds(), 0)?.with_timezone(&offset))
+}
+
+/// Current branch of the repository containing `path`, `HEAD` when detached
+pub fn branch(path: &Path) -> Option<String> {
+    with_repo(path, |repo| {
+        repo.repo.head().ok()?.shorthand().map(str::to_string)
+    })
+}
+
+/// URL of `remote` in the repository containing `path`
+pub fn remote_url(path: &Path, remote: &str) -> Option<String> {
+    with_repo(path, |repo| {
+        repo.repo
+            .find_remote(remote)
+            .ok()?
+            .url()
+            .map(str::to_string)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use git2::{Signature, Tree};
+
+    use super::*;
+
+    fn commit(repo: &Repository, author: &str, seconds: i64, tree: &Tree) {
+        let signature = Signature::new(author, "a@b.c", &Time::new(seconds, 60)).unwrap();
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<_> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &signature, &signature, "", tree, &parents)
+            .unwrap();
+    }
+
+    #[test]
+    fn history_follows_renames() {
+        let dir = env::temp_dir().join(format!("dev-header-git-{}", process::id()));
+        let repo = Repository::init(&dir).unwrap();
+        let mut index = repo.index().unwrap();
+        let mut step = |author: &str, seconds: i64, write: &dyn Fn()| {
+            write();
+            index
+                .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+                .unwrap();
+            index.update_all(["*"], None).unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            commit(&repo, author, seconds, &tree);
+        };
+        step("Alice", 1_000_000_000, &|| {
+            fs::write(dir.join("old.py"), "print(1)\nprint(2)\n").unwrap()
+        });
+        step("Bob", 1_100_000_000, &|| {
+            fs::rename(dir.join("old.py"), dir.join("new.py")).unwrap()
+        });
+        step("Carol", 1_200_000_000, &|| {
+            fs::write(dir.join("new.py"), "print(1)\nprint(2)\nprint(3)\n").unwrap()
+        });
+
+        let history = read_history(&repo);
+        fs::remove_dir_all(&dir).unwrap();
+        let file = &history[Path::new("new.py")];
+        let file = FileHistory {
+            commits: file.clone(),
+        };
+        assert_eq!(file.contributors(), ["Alice", "Bob", "Carol"]);
+        assert_eq!(file.created().unwrap().date.timestamp(), 1_000_000_000);
+        assert_eq!(
+            file.created().unwrap().date.offset().local_minus_utc(),
+            3600
+        );
+        assert_eq!(file.modified().unwrap().author, "Carol");
+        assert!(!history.contains_key(Path::new("old.py")));
+    }
+}
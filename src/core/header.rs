@@ -1,73 +1,183 @@
-use arboard::Clipboard;
-use dialoguer::{Input, theme::ColorfulTheme};
+use std::path::Path;
 
-use crate::core::template::{Alignment, Field, Template};
+use dialoguer::{Input, Select, theme::ColorfulTheme};
 
-pub fn border_input(promt: &str, input_theme: &ColorfulTheme) -> Option<char> {
+use crate::{
+    core::{
+        copyright::{self, Years},
+        output::Output,
+        placeholder::{Context, Token},
+        template::{Alignment, Border, Field, FieldKind, ListStyle, Template, YearSource},
+        text::{align_lines, pad, repeat, width, wrap_text},
+    },
+    error::{Error, Result},
+};
+
+pub fn border_input(promt: &str, input_theme: &ColorfulTheme) -> Result<Option<String>> {
     let value: String = Input::with_theme(input_theme)
         .with_prompt(promt)
         .allow_empty(true)
-        .validate_with(|input: &String| -> Result<(), &str> {
-            if input.is_empty() || input.chars().count() == 1 {
-                Ok(())
+        .validate_with(|input: &String| -> std::result::Result<(), &str> {
+            if input.contains(char::is_control) {
+                Err("Please enter characters without control characters or leave empty")
             } else {
-                Err("Please enter exactly one character or leave empty")
+                Ok(())
             }
         })
-        .interact_text()
-        .unwrap();
-    if value.is_empty() {
-        None
-    } else {
-        Some(value.chars().next().unwrap())
-    }
+        .interact_text()?;
+    Ok(Some(value).filter(|value| !value.is_empty()))
 }
 
-pub fn get_header(template: &Template) {
-    let header = build_header(template);
-    println!("\n{}\n", header);
+/// Pick a border preset or enter every side and corner, `None` for no border
+pub fn border_select(input_theme: &ColorfulTheme) -> Result<Option<Border>> {
+    let mut items = Border::preset_names();
+    items.extend(["custom", "none"]);
+    let selection = Select::with_theme(input_theme)
+        .with_prompt("Border")
+        .items(&items)
+        .default(0)
+        .interact()?;
+    match items[selection] {
+        "none" => Ok(None),
+        "custom" => {
+            let border = Border {
+                left: border_input("Left", input_theme)?,
+                top: border_input("Top", input_theme)?,
+                right: border_input("Right", input_theme)?,
+                bottom: border_input("Bottom", input_theme)?,
+                top_left: border_input("Top left corner", input_theme)?,
+                top_right: border_input("Top right corner", input_theme)?,
+                bottom_left: border_input("Bottom left corner", input_theme)?,
+                bottom_right: border_input("Bottom right corner", input_theme)?,
+            };
+            Ok(Some(border).filter(|border| !border.is_empty()))
+        }
+        name => Ok(Border::preset(name)),
+    }
+}
 
-    // copy to clipboard
-    let mut clipboard = Clipboard::new().unwrap();
-    clipboard.set_text(header).unwrap();
+/// Pick the kind of a field, and the list style for lists
+pub fn kind_select(field: &mut Field, input_theme: &ColorfulTheme) -> Result<()> {
+    let current = FieldKind::ALL
+        .iter()
+        .position(|kind| *kind == field.kind)
+        .unwrap_or(0);
+    let kind = Select::with_theme(input_theme)
+        .with_prompt("Kind")
+        .items(FieldKind::ALL.map(|kind| kind.name()))
+        .default(current)
+        .interact()?;
+    field.kind = FieldKind::ALL[kind].clone();
+    if field.kind == FieldKind::Copyright {
+        let current = YearSource::ALL
+            .iter()
+            .position(|source| *source == field.copyright_year)
+            .unwrap_or(0);
+        let source = Select::with_theme(input_theme)
+            .with_prompt("Extend the years to")
+            .items(YearSource::ALL.map(|source| source.name()))
+            .default(current)
+            .interact()?;
+        field.copyright_year = YearSource::ALL[source].clone();
+    }
+    if field.kind == FieldKind::List {
+        let current = ListStyle::ALL
+            .iter()
+            .position(|style| *style == field.list_style)
+            .unwrap_or(0);
+        let style = Select::with_theme(input_theme)
+            .with_prompt("List style")
+            .items(ListStyle::ALL.map(|style| style.name()))
+            .default(current)
+            .interact()?;
+        field.list_style = ListStyle::ALL[style].clone();
+    }
+    Ok(())
 }
 
-pub fn build_header(template: &Template) -> String {
-    // calculate key & value size
-    let key_len = {
-        let mut max = 0;
-        for field in &template.fields {
-            if field.key_visible && max < field.key.len() {
-                max = field.key.len();
-            }
+/// Prompt for the value of `field`, lines and list items are asked one by one
+pub fn value_input(field: &Field, input_theme: &ColorfulTheme) -> Result<String> {
+    let (hint, done): (&str, fn(&str) -> bool) = match field.kind {
+        FieldKind::Multiline => ("`.` to finish", |line| line == "."),
+        FieldKind::List => ("empty to finish", str::is_empty),
+        _ => {
+            return Ok(Input::with_theme(input_theme)
+                .with_prompt(field.key.clone())
+                .interact()?);
         }
-        max
     };
-    let max_len = (template.length - 2) as usize; // exclude left, right border
-    let value_len = max_len - key_len - 4; // space before key: 1     space after key: 3 (' : ')
+    let mut lines = vec![];
+    loop {
+        let line: String = Input::with_theme(input_theme)
+            .with_prompt(format!("{} {} ({})", field.key, lines.len() + 1, hint))
+            .allow_empty(true)
+            .interact_text()?;
+        if done(&line) {
+            break;
+        }
+        lines.push(line);
+    }
+    Ok(lines.join("\n"))
+}
+
+/// Years of `value` and of the `nth` notice of `previous`, extended to the year
+/// picked by the field, followed by the rest of `value`
+fn copyright_notice(
+    field: &Field,
+    context: &Context,
+    value: &str,
+    previous: Option<&str>,
+    nth: usize,
+) -> String {
+    let (mut years, holder) = Years::split(value);
+    if let Some(previous) = previous {
+        years.merge(&Years::find(previous, nth));
+    }
+    let token = match field.copyright_year {
+        YearSource::Now => "year",
+        YearSource::Git => "git.modified:%Y",
+    };
+    if let Some(year) = context
+        .resolve(&Token::parse(token))
+        .and_then(|year| year.parse().ok())
+    {
+        years.extend_to(year);
+    }
+    copyright::notice(&years, holder)
+}
+
+/// Render `template` for the file at `path`, if any, and send it to `output`
+pub fn get_header(template: &Template, path: Option<&Path>, output: &Output) -> Result<()> {
+    template.check_layout().map_err(Error::Render)?;
+    let header = build_header(template, &Context::new(path));
+    output.write(&header)
+}
+
+pub fn build_header(template: &Template, context: &Context) -> String {
+    render_header(template, context, None)
+}
 
-    // println!("key_len: {}", key_len);
-    // println!("value_len: {}", value_len);
-    // println!("max_len: {}", max_len);
+/// Render `template` in place of the `previous` header, whose copyright years are kept
+pub fn render_header(template: &Template, context: &Context, previous: Option<&str>) -> String {
+    let max_len = template.body_len(); // exclude comment prefix, left, right border
 
     // build header body
     let mut lines: Vec<String> = vec![];
+    let mut copyrights = 0;
     for field in &template.fields {
-        let vec_lines = if field.key_visible {
-            build_key_value_lines(field, key_len, value_len)
-        } else {
-            build_line(field, max_len)
-        };
-
-        for line in vec_lines {
-            lines.push(line);
+        let mut value = context.render(&field.default_value);
+        if field.kind == FieldKind::Copyright {
+            value = copyright_notice(field, context, &value, previous, copyrights);
+            copyrights += 1;
         }
-
-        // println!("|{}|", line);
-        // println!("size: {}", line.len());
+        let field = &Field {
+            default_value: value,
+            ..field.clone()
+        };
+        lines.extend(field_lines(template, field, max_len));
     }
     // add space
-    let empty_line = format!("{}", " ".repeat(max_len));
+    let empty_line = " ".repeat(max_len);
     for _ in 0..template.space_after {
         lines.push(empty_line.clone());
     }
@@ -75,132 +185,491 @@ pub fn build_header(template: &Template) -> String {
         lines.insert(0, empty_line.clone());
     }
 
-    // add borders
-    if let Some(border) = &template.border {
-        // left
-        if let Some(left) = border.left {
-            for line in &mut lines {
-                line.insert(0, left);
+    // add borders and wrap into a comment
+    let frame = header_frame(template);
+    let mut lines: Vec<String> = lines
+        .iter()
+        .map(|line| body_line(template, &frame, line))
+        .collect();
+    if let Some(top) = frame.top {
+        lines.insert(0, top);
+    }
+    if let Some(bottom) = frame.bottom {
+        lines.push(bottom);
+    }
+
+    lines.join("\n")
+}
+
+/// Lines of `field`, whose value is already rendered, `max_len` columns wide
+fn field_lines(template: &Template, field: &Field, max_len: usize) -> Vec<String> {
+    let line_len = max_len.saturating_sub(field.padding());
+    // lines without a key start with a space too, like the one before a key,
+    // so that `find_header` tells them from a comment following the header
+    let lines = if field.key_visible && !field.kind.is_separator() {
+        build_key_value_lines(field, template, line_len)
+    } else {
+        let line_len = line_len.saturating_sub(1);
+        let lines = match field.kind.is_separator() {
+            true => build_separator(field, line_len),
+            false => build_line(field, line_len),
+        };
+        lines.into_iter().map(|line| format!(" {}", line)).collect()
+    };
+
+    let left = " ".repeat(field.padding_left as usize);
+    let right = " ".repeat(field.padding_right as usize);
+    lines
+        .into_iter()
+        .map(|line| format!("{}{}{}", left, line, right))
+        .collect()
+}
+
+/// `line` of the body between the left and right border, after the comment prefix
+fn body_line(template: &Template, frame: &Frame, line: &str) -> String {
+    let (left, right) = match &template.border {
+        Some(border) => (
+            border.left.as_deref().unwrap_or_default(),
+            border.right.as_deref().unwrap_or_default(),
+        ),
+        None => ("", ""),
+    };
+    format!("{}{}{}{}", frame.prefix, left, line, right)
+}
+
+/// Comment lines around the header body, derived from the border and comment style
+pub struct Frame {
+    pub top: Option<String>,
+    pub bottom: Option<String>,
+    /// Prepended to every body line
+    pub prefix: String,
+}
+
+/// `start`, then `pattern` repeated, then `end`, `length` columns in total
+fn rule(start: &str, pattern: &str, end: &str, length: usize) -> String {
+    let fill = length.saturating_sub(width(start) + width(end));
+    format!("{}{}{}", start, repeat(pattern, fill), end)
+}
+
+pub fn header_frame(template: &Template) -> Frame {
+    let style = template.comment_style();
+    let length = template.length as usize;
+    match (&template.border, style.open, style.close) {
+        (Some(border), Some(open), Some(close)) => {
+            let top = match &border.top {
+                Some(top) => rule(
+                    &format!("{}{}", open, border.top_left.as_deref().unwrap_or_default()),
+                    top,
+                    border.top_right.as_deref().unwrap_or_default(),
+                    length,
+                ),
+                None => open.to_string(),
+            };
+            let bottom = match &border.bottom {
+                Some(bottom) => rule(
+                    border.bottom_left.as_deref().unwrap_or_default(),
+                    bottom,
+                    &format!(
+                        "{}{}",
+                        border.bottom_right.as_deref().unwrap_or_default(),
+                        close
+                    ),
+                    length,
+                ),
+                None => close.to_string(),
+            };
+            // continuation is only drawn when there is no left border
+            let prefix = match border.left {
+                Some(_) => String::new(),
+                None => style.continuation.unwrap_or_default().to_string(),
+            };
+            Frame {
+                top: Some(top),
+                bottom: Some(bottom),
+                prefix,
             }
         }
-
-        // right
-        if let Some(right) = border.right {
-            for line in &mut lines {
-                line.insert(line.len(), right);
+        // no block comment for this style: draw top and bottom as comment lines
+        (Some(border), _, _) => {
+            let prefix = style.line.unwrap_or_default();
+            let length = length.saturating_sub(width(prefix));
+            let corner =
+                |corner: &Option<String>| corner.as_deref().unwrap_or_default().to_string();
+            Frame {
+                top: border.top.as_ref().map(|top| {
+                    let line = rule(
+                        &corner(&border.top_left),
+                        top,
+                        &corner(&border.top_right),
+                        length,
+                    );
+                    format!("{}{}", prefix, line)
+                }),
+                bottom: border.bottom.as_ref().map(|bottom| {
+                    let line = rule(
+                        &corner(&border.bottom_left),
+                        bottom,
+                        &corner(&border.bottom_right),
+                        length,
+                    );
+                    format!("{}{}", prefix, line)
+                }),
+                prefix: prefix.to_string(),
             }
         }
+        (None, open, close) => match style.line {
+            Some(prefix) => Frame {
+                top: None,
+                bottom: None,
+                prefix: prefix.to_string(),
+            },
+            None => Frame {
+                top: Some(open.unwrap_or_default().to_string()),
+                bottom: Some(close.unwrap_or_default().to_string()),
+                prefix: style.continuation.unwrap_or_default().to_string(),
+            },
+        },
+    }
+}
 
-        // top
-        if let Some(top) = border.top {
-            lines.insert(0, format!("/*{}", String::from(top).repeat(max_len)));
-        } else {
-            lines.insert(0, format!("/*"));
+/// Byte length of a header previously generated from `template` at the start of `text`.
+/// Without a top or bottom border line the block must also contain the visible keys of
+/// `template`, or without keys its lines that don't change, so that ordinary comments
+/// are never taken for a header.
+pub fn find_header(template: &Template, text: &str) -> Option<usize> {
+    let len = frame_len(template, text)?;
+    let block = &text[..len];
+    let has_rule = template
+        .border
+        .as_ref()
+        .is_some_and(|border| border.top.is_some() || border.bottom.is_some());
+    let has_keys = template.value_fields().any(|field| field.key_visible);
+    let found = match (has_rule, has_keys) {
+        (true, _) => true,
+        (false, true) => matches_keys(template, block),
+        (false, false) => matches_static_lines(template, block),
+    };
+    found.then_some(len)
+}
+
+/// Byte length of the lines at the start of `text` drawn like the frame of `template`
+fn frame_len(template: &Template, text: &str) -> Option<usize> {
+    let frame = header_frame(template);
+    let left = template
+        .border
+        .as_ref()
+        .and_then(|border| border.left.as_deref());
+    let same = |line: &str, expected: &str| line.trim_end() == expected.trim_end();
+    // without an opening line, every line must look like a rendered body line
+    let is_body = |line: &str| {
+        let line = line.trim_end_matches(['\r', '\n']);
+        match line.strip_prefix(frame.prefix.as_str()) {
+            Some(rest) if !frame.prefix.is_empty() => {
+                rest.is_empty()
+                    || rest.starts_with(' ')
+                    || left.is_some_and(|c| rest.starts_with(c))
+            }
+            _ => false,
         }
+    };
 
-        // bottom
-        if let Some(bottom) = border.bottom {
-            lines.push(format!("{}*/", String::from(bottom).repeat(max_len)));
-        } else {
-            lines.push(format!("*/"));
+    let mut lines = text.split_inclusive('\n');
+    let mut end = 0;
+    if let Some(top) = &frame.top {
+        let line = lines.next()?;
+        if !same(line, top) {
+            return None;
+        }
+        end += line.len();
+    }
+    for line in lines {
+        if frame
+            .bottom
+            .as_ref()
+            .is_some_and(|bottom| same(line, bottom))
+        {
+            return Some(end + line.len());
+        }
+        if frame.top.is_none() && !is_body(line) {
+            break;
         }
+        end += line.len();
+    }
+    if frame.bottom.is_none() && end > 0 {
+        Some(end)
     } else {
-        for line in &mut lines {
-            line.insert_str(0, "//");
+        None
+    }
+}
+
+/// Whether the visible keys of `template` appear in order in `block`, each followed
+/// by the separator. False for templates without visible keys.
+fn matches_keys(template: &Template, block: &str) -> bool {
+    let separator = template.separator.trim();
+    let keys: Vec<&str> = template
+        .value_fields()
+        .filter(|field| field.key_visible)
+        .map(|field| field.key.as_str())
+        .collect();
+    if keys.is_empty() {
+        return false;
+    }
+    let mut rest = block;
+    for key in keys {
+        // keys are padded with spaces up to the separator
+        let next = rest.match_indices(key).find_map(|(idx, _)| {
+            rest[idx + key.len()..]
+                .trim_start_matches(' ')
+                .strip_prefix(separator)
+        });
+        match next {
+            Some(next) => rest = next,
+            None => return false,
         }
     }
+    true
+}
 
-    lines.join("\n")
+/// Whether the lines of values without placeholders and of headings appear in order
+/// in `block`. False when every line of `template` may change.
+fn matches_static_lines(template: &Template, block: &str) -> bool {
+    let frame = header_frame(template);
+    let max_len = template.body_len();
+    let expected: Vec<String> = template
+        .fields
+        .iter()
+        .filter(|field| match field.kind {
+            FieldKind::Rule | FieldKind::Blank | FieldKind::Copyright => false,
+            _ => !field.default_value.trim().is_empty() && !field.default_value.contains("{{"),
+        })
+        .flat_map(|field| field_lines(template, field, max_len))
+        .map(|line| body_line(template, &frame, &line).trim_end().to_string())
+        .collect();
+    if expected.is_empty() {
+        return false;
+    }
+    let mut lines = block.lines().map(str::trim_end);
+    expected
+        .iter()
+        .all(|expected| lines.any(|line| line == expected))
 }
 
-fn build_line(field: &Field, line_len: usize) -> Vec<String> {
-    let lines = wrap_text(&field.default_value, line_len);
-    let mut result = vec![];
-    for i in 0..lines.len() {
-        let line = match field.align {
-            Alignment::Center => format!("{:^line_len$}", lines[i]),
-            Alignment::Left => format!("{:<line_len$}", lines[i]),
-        };
-        result.push(line);
+fn build_separator(field: &Field, line_len: usize) -> Vec<String> {
+    match field.kind {
+        FieldKind::Rule => {
+            let pattern = match field.default_value.as_str() {
+                "" => "-",
+                pattern => pattern,
+            };
+            vec![repeat(pattern, line_len)]
+        }
+        FieldKind::Heading => align_lines(
+            &wrap_text(&field.default_value, line_len),
+            line_len,
+            &field.align,
+        ),
+        _ => vec![" ".repeat(line_len)],
     }
-    result
 }
 
-pub fn build_key_value_lines(field: &Field, key_len: usize, value_len: usize) -> Vec<String> {
-    let mut result = vec![];
-    let lines = wrap_text(&field.default_value, value_len.into());
+fn build_line(field: &Field, line_len: usize) -> Vec<String> {
+    value_lines(field, line_len)
+}
 
-    // println!("Lines wrapped: {:?}", lines);
+/// Lines of a field with a visible key, keys are padded to the key column of their group
+pub fn build_key_value_lines(field: &Field, template: &Template, line_len: usize) -> Vec<String> {
+    let key_len = template.key_len(field);
+    let key_column = template.key_column(field);
+    // see `Template::check_layout`
+    let value_len = line_len.saturating_sub(key_column);
 
-    for i in 0..lines.len() {
-        let line = {
+    value_lines(field, value_len)
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| {
             let key = if i == 0 {
-                format!(" {:<key_len$} : ", field.key.clone())
+                format!(
+                    " {}{}",
+                    pad(&field.key, key_len, &Alignment::Left),
+                    template.separator
+                )
             } else {
-                format!(" {:<key_len$}   ", " ".repeat(key_len))
+                " ".repeat(key_column)
             };
+            format!("{}{}", key, line)
+        })
+        .collect()
+}
 
-            match field.align {
-                Alignment::Center => {
-                    format!("{}{:^value_len$}", key, lines[i])
-                }
-                Alignment::Left => {
-                    format!("{}{:<value_len$}", key, lines[i])
-                }
-            }
-        };
-        result.push(line);
-    }
-    result
-}
-
-pub fn wrap_text(text: &str, line_len: usize) -> Vec<String> {
-    let mut result: Vec<String> = vec![];
-    let mut line = String::from("");
-
-    // println!("line_len: {line_len}");
-
-    for word in text.split_whitespace() {
-        // current word is longer than the remain space and longer than total space
-        if word.len() > (line_len - line.len()) && word.len() > line_len {
-            // split to fit the remain space
-            let remain = line_len - line.len();
-            let s = &word[0..remain];
-            line.push_str(s);
-            result.push(line.clone());
-
-            // number of lines for the remaining character
-            let n = ((word.len() - remain) as f32 / line_len as f32).ceil() as usize;
-            let mut start_pos = remain;
-            let mut end_pos = std::cmp::min(start_pos + line_len, word.len());
-            let mut i = 0;
-            loop {
-                let s = &word[start_pos..end_pos];
-                line = s.to_string();
-                if i >= n - 1 {
-                    line.push(' ');
-                    break;
-                }
-                result.push(s.to_string());
-                start_pos = end_pos;
-                end_pos = std::cmp::min(start_pos + line_len, word.len());
-                i += 1;
-            }
+/// Value of `field` laid out according to its kind, every line `len` columns wide
+fn value_lines(field: &Field, len: usize) -> Vec<String> {
+    let value = &field.default_value;
+    let lines: Vec<String> = match (&field.kind, &field.list_style) {
+        // an empty line stays a blank line
+        (FieldKind::Multiline, _) => value
+            .lines()
+            .flat_map(|line| align_lines(&wrap_text(line, len), len, &field.align))
+            .collect(),
+        (FieldKind::List, ListStyle::Comma) => {
+            let value = Field::items(value).join(", ");
+            align_lines(&wrap_text(&value, len), len, &field.align)
         }
-        // current word is shorter
-        else if line.len() + word.len() + 1 <= line_len {
-            line.push_str(word);
-            line.push(' ');
+        (FieldKind::List, ListStyle::Bullet) => {
+            let indent = width(Field::BULLET);
+            let item_len = len.saturating_sub(indent);
+            Field::items(value)
+                .into_iter()
+                .flat_map(|item| {
+                    align_lines(&wrap_text(item, item_len), item_len, &field.align)
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, line)| match i {
+                            0 => format!("{}{}", Field::BULLET, line),
+                            _ => format!("{}{}", " ".repeat(indent), line),
+                        })
+                })
+                .collect()
         }
-        // line complete
-        else {
-            result.push(line);
-            line = word.to_string();
-            if line.len() < line_len {
-                line.push(' ');
+        _ => align_lines(&wrap_text(value, len), len, &field.align),
+    };
+    if lines.is_empty() {
+        vec![" ".repeat(len)]
+    } else {
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::core::comment::CommentStyle;
+
+    fn template(comment: &str, border: serde_json::Value) -> Template {
+        serde_json::from_value(json!({
+            "name": "test",
+            "length": 40,
+            "comment": comment,
+            "border": border,
+            "fields": [
+                { "key": "File", "default_value": "a.py", "key_visible": true },
+                { "key": "Purpose", "default_value": "Tests", "key_visible": true },
+            ],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn finds_rendered_header() {
+        let template = template("hash", json!(null));
+        let header = build_header(&template, &Context::new(None));
+        let text = format!("{}\n\nprint(1)\n", header);
+        assert_eq!(find_header(&template, &text), Some(header.len() + 1));
+    }
+
+    #[test]
+    fn ignores_ordinary_line_comments() {
+        let template = template("hash", json!(null));
+        let text = "# Important: do not remove this license note\n# second line\nprint(1)\n";
+        assert_eq!(find_header(&template, text), None);
+    }
+
+    #[test]
+    fn ignores_block_comments_without_keys() {
+        let template = template("c", json!({ "left": " * " }));
+        let text = "/*\n * Copyright notice\n */\nint main() {}\n";
+        assert_eq!(find_header(&template, text), None);
+    }
+
+    #[test]
+    fn needs_keys_in_order() {
+        let template = template("hash", json!(null));
+        let text = "# Purpose : Tests\n# File : a.py\n";
+        assert_eq!(find_header(&template, text), None);
+        let text = "# File    : a.py\n# Purpose : Tests\n";
+        assert_eq!(find_header(&template, text), Some(text.len()));
+    }
+
+    #[test]
+    fn border_lines_are_enough() {
+        let template = template("c", json!({ "top": "*", "bottom": "*" }));
+        let header = build_header(&template, &Context::new(None));
+        let edited = header.replace("Purpose", "Goal");
+        assert_eq!(find_header(&template, &edited), Some(edited.len()));
+    }
+
+    #[test]
+    fn every_line_is_length_columns() {
+        let borders = [
+            json!(null),
+            json!({ "left": "*" }),
+            json!({ "left": "| ", "right": " |", "top": "-", "bottom": "=" }),
+            serde_json::to_value(Border::preset("double")).unwrap(),
+        ];
+        for style in CommentStyle::BUILTIN {
+            for border in &borders {
+                let template: Template = serde_json::from_value(json!({
+                    "name": "test",
+                    "length": 40,
+                    "comment": style.name,
+                    "border": border,
+                    "fields": [
+                        { "key": "File", "default_value": "a.py", "key_visible": true },
+                        { "key": "Note", "default_value": "hidden key", "key_visible": false },
+                        { "key": "", "default_value": "", "kind": "rule" },
+                    ],
+                }))
+                .unwrap();
+                let header = build_header(&template, &Context::new(None));
+                for line in header.lines() {
+                    // the comment delimiters stand alone without a top or bottom border
+                    if style.open == Some(line) || style.close == Some(line) {
+                        continue;
+                    }
+                    assert_eq!(width(line), 40, "{} {}: {:?}", style.name, border, line);
+                }
             }
         }
     }
-    result.push(line);
-    result
+
+    fn field(kind: &str, list_style: &str, value: &str) -> Field {
+        serde_json::from_value(json!({
+            "key": "Notes",
+            "default_value": value,
+            "kind": kind,
+            "list_style": list_style,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn multiline_keeps_line_breaks_and_blank_lines() {
+        let field = field("multiline", "bullet", "first line\n\nthird  line wraps");
+        assert_eq!(
+            value_lines(&field, 10),
+            vec!["first line", "          ", "third line", "wraps     "]
+        );
+    }
+
+    #[test]
+    fn bullet_list_indents_wrapped_items() {
+        let field = field("list", "bullet", "one, two three four");
+        assert_eq!(
+            value_lines(&field, 10),
+            vec!["- one     ", "- two     ", "  three   ", "  four    "]
+        );
+    }
+
+    #[test]
+    fn comma_list_joins_items() {
+        let field = field("list", "comma", "one\n\n two \nthree");
+        assert_eq!(value_lines(&field, 16), vec!["one, two, three "]);
+    }
+
+    #[test]
+    fn empty_value_is_one_blank_line() {
+        let field = field("list", "bullet", " , ");
+        assert_eq!(value_lines(&field, 3), vec!["   "]);
+    }
 }
@@ -0,0 +1,112 @@
+use spdx::Expression;
+
+use crate::error::{Error, Result};
+
+/// Short notice of a license, to be put in every file
+pub struct License {
+    pub id: &'static str,
+    pub notice: &'static str,
+}
+
+impl License {
+    /// Offline catalogue of common licenses
+    pub const CATALOGUE: [License; 5] = [
+        License {
+            id: "MIT",
+            notice: "Use of this source code is governed by the MIT license that can be \
+                     found in the LICENSE file or at https://opensource.org/licenses/MIT.",
+        },
+        License {
+            id: "Apache-2.0",
+            notice: "Licensed under the Apache License, Version 2.0 (the \"License\"); \
+                     you may not use this file except in compliance with the License. \
+                     You may obtain a copy of the License at\n\n\
+                     http://www.apache.org/licenses/LICENSE-2.0\n\n\
+                     Unless required by applicable law or agreed to in writing, software \
+                     distributed under the License is distributed on an \"AS IS\" BASIS, \
+                     WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. \
+                     See the License for the specific language governing permissions and \
+                     limitations under the License.",
+        },
+        License {
+            id: "GPL-3.0-or-later",
+            notice: "This program is free software: you can redistribute it and/or modify \
+                     it under the terms of the GNU General Public License as published by \
+                     the Free Software Foundation, either version 3 of the License, or \
+                     (at your option) any later version.\n\n\
+                     This program is distributed in the hope that it will be useful, \
+                     but WITHOUT ANY WARRANTY; without even the implied warranty of \
+                     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the \
+                     GNU General Public License for more details.\n\n\
+                     You should have received a copy of the GNU General Public License \
+                     along with this program. If not, see <https://www.gnu.org/licenses/>.",
+        },
+        License {
+            id: "BSD-3-Clause",
+            notice: "Use of this source code is governed by a BSD-style license that can be \
+                     found in the LICENSE file or at https://opensource.org/licenses/BSD-3-Clause.",
+        },
+        License {
+            id: "MPL-2.0",
+            notice: "This Source Code Form is subject to the terms of the Mozilla Public \
+                     License, v. 2.0. If a copy of the MPL was not distributed with this \
+                     file, You can obtain one at https://mozilla.org/MPL/2.0/.",
+        },
+    ];
+
+    pub fn get(id: &str) -> Option<&'static License> {
+        Self::CATALOGUE.iter().find(|license| license.id == id)
+    }
+}
+
+/// Check an SPDX expression such as `MIT OR Apache-2.0`
+pub fn validate(expr: &str) -> Result<Expression> {
+    Expression::parse(expr.trim())
+        .map_err(|e| Error::Validation(format!("invalid SPDX expression `{}`: {}", expr, e)))
+}
+
+/// `SPDX-License-Identifier: <expr>`
+pub fn identifier(expr: &str) -> Result<String> {
+    let expr = validate(expr)?;
+    Ok(format!("SPDX-License-Identifier: {}", expr.as_ref()))
+}
+
+/// Notices of the catalogued licenses of `expr`, separated by a blank line
+pub fn notice(expr: &str) -> Result<String> {
+    let expr = validate(expr)?;
+    let mut notices: Vec<&str> = vec![];
+    for req in expr.requirements() {
+        if let Some(license) = License::get(&req.req.license.to_string())
+            && !notices.contains(&license.notice)
+        {
+            notices.push(license.notice);
+        }
+    }
+    Ok(notices.join("\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notice_of_every_license_in_order() {
+        let mit = License::get("MIT").unwrap().notice;
+        let apache = License::get("Apache-2.0").unwrap().notice;
+        assert_eq!(
+            notice("MIT OR Apache-2.0").unwrap(),
+            format!("{}\n\n{}", mit, apache)
+        );
+        // repeated and uncatalogued licenses add nothing
+        assert_eq!(notice("MIT AND (MIT OR Zlib)").unwrap(), mit);
+    }
+
+    #[test]
+    fn identifier_checks_the_expression() {
+        assert_eq!(
+            identifier(" MIT OR Apache-2.0 ").unwrap(),
+            "SPDX-License-Identifier: MIT OR Apache-2.0"
+        );
+        assert!(matches!(notice("MIT OR"), Err(Error::Validation(_))));
+    }
+}
@@ -1,3 +1,14 @@
+pub mod comment;
+pub mod config;
+pub mod copyright;
+pub mod git;
 pub mod header;
+pub mod license;
+pub mod output;
+pub mod placeholder;
+pub mod rule;
+pub mod source;
+pub mod spec;
 pub mod storage;
 pub mod template;
+pub mod text;
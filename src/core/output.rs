@@ -0,0 +1,100 @@
+use std::{
+    env, fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use arboard::Clipboard;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use clap::ValueEnum;
+use console::style;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sink {
+    /// Print and copy to the system clipboard
+    Clipboard,
+    /// Print only
+    Stdout,
+    /// Write to the file given with --file
+    File,
+    /// Print and copy through the terminal (OSC 52), works over SSH
+    Osc52,
+}
+
+/// Where rendered headers go
+#[derive(Debug, Clone, Default)]
+pub struct Output {
+    /// `None` means clipboard, falling back to stdout when there is no clipboard
+    pub sink: Option<Sink>,
+    pub file: Option<PathBuf>,
+}
+
+impl Output {
+    pub fn write(&self, header: &str) -> Result<()> {
+        let sink = match (self.sink, &self.file) {
+            (Some(sink), _) => sink,
+            (None, Some(_)) => Sink::File,
+            (None, None) => Sink::Clipboard,
+        };
+        match sink {
+            Sink::Stdout => {
+                println!("{}", header);
+                Ok(())
+            }
+            Sink::File => {
+                let Some(path) = &self.file else {
+                    return Err(Error::Validation(
+                        "--output file requires --file <PATH>".to_string(),
+                    ));
+                };
+                fs::write(path, format!("{}\n", header)).map_err(|e| Error::io(path, e))?;
+                println!("{} {}", style("Written to").green().bold(), path.display());
+                Ok(())
+            }
+            Sink::Osc52 => {
+                println!("\n{}\n", header);
+                osc52(header)
+            }
+            Sink::Clipboard => {
+                println!("\n{}\n", header);
+                match copy(header) {
+                    // only an explicit --output clipboard fails without a clipboard
+                    Err(e) if self.sink.is_none() => {
+                        eprintln!(
+                            "{}: {}, header printed only",
+                            style("warning").yellow().bold(),
+                            e
+                        );
+                        Ok(())
+                    }
+                    result => result,
+                }
+            }
+        }
+    }
+}
+
+fn copy(header: &str) -> Result<()> {
+    let mut clipboard = Clipboard::new()?;
+    clipboard.set_text(header)?;
+    Ok(())
+}
+
+/// Ask the terminal to set the clipboard, wrapped for tmux when needed
+fn osc52(header: &str) -> Result<()> {
+    let sequence = format!("\x1b]52;c;{}\x07", STANDARD.encode(header));
+    let sequence = if env::var_os("TMUX").is_some() {
+        format!("\x1bPtmux;\x1b{}\x1b\\", sequence)
+    } else {
+        sequence
+    };
+    let mut stdout = io::stdout();
+    stdout
+        .write_all(sequence.as_bytes())
+        .and_then(|_| stdout.flush())
+        .map_err(|e| Error::Clipboard(e.to_string()))
+}
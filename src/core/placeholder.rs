@@ -0,0 +1,340 @@
+use std::{
+    cell::OnceCell,
+    env,
+    path::{Path, PathBuf},
+};
+
+use chrono::{
+    DateTime, FixedOffset, Local, NaiveDate, Utc,
+    format::{Item, StrftimeItems},
+};
+
+use crate::core::{
+    config::Config,
+    git::{self, FileHistory},
+    license,
+};
+
+/// A `{{name.arg:format}}` token found in a field value
+#[derive(Debug, PartialEq)]
+pub struct Token<'a> {
+    pub name: &'a str,
+    pub arg: Option<&'a str>,
+    pub format: Option<&'a str>,
+}
+
+impl<'a> Token<'a> {
+    pub fn parse(token: &'a str) -> Token<'a> {
+        // the format is kept as is, it may start or end with spaces
+        let (token, format) = match token.split_once(':') {
+            Some((token, format)) => (token.trim(), Some(format)),
+            None => (token.trim(), None),
+        };
+        let (name, arg) = match token.split_once('.') {
+            Some((name, arg)) => (name, Some(arg)),
+            None => (token, None),
+        };
+        Token { name, arg, format }
+    }
+}
+
+pub type Resolver = Box<dyn Fn(&Context, &Token) -> Option<String>>;
+
+/// Rendering context: the target file and the placeholder resolvers
+pub struct Context {
+    pub path: Option<PathBuf>,
+    resolvers: Vec<(String, Resolver)>,
+    history: OnceCell<Option<FileHistory>>,
+}
+
+impl Context {
+    pub fn new(path: Option<&Path>) -> Self {
+        let mut context = Context {
+            path: path.map(Path::to_path_buf),
+            resolvers: vec![],
+            history: OnceCell::new(),
+        };
+        context.register("file_name", |ctx, _| {
+            ctx.path
+                .as_ref()?
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+        });
+        context.register("file_stem", |ctx, _| {
+            ctx.path
+                .as_ref()?
+                .file_stem()
+                .map(|name| name.to_string_lossy().to_string())
+        });
+        context.register("file_path", |ctx, _| {
+            ctx.path
+                .as_ref()
+                .map(|path| path.to_string_lossy().to_string())
+        });
+        context.register("date", |_, _| format_date(now(), false, "%Y-%m-%d"));
+        context.register("year", |_, _| format_date(now(), false, "%Y"));
+        // {{now:%d %b %Y}}, {{now.utc:%H:%M}}
+        context.register("now", |_, token| {
+            let utc = match token.arg {
+                None | Some("local") => false,
+                Some("utc") => true,
+                Some(_) => return None,
+            };
+            format_date(now(), utc, token.format.unwrap_or("%Y-%m-%d"))
+        });
+        context.register("author", |ctx, _| {
+            Config::current()
+                .author
+                .clone()
+                .or_else(|| git::config(ctx.dir(), "user.name"))
+                .or_else(|| env::var("USER").ok())
+                .or_else(|| env::var("USERNAME").ok())
+        });
+        context.register("email", |ctx, _| {
+            Config::current()
+                .email
+                .clone()
+                .or_else(|| git::config(ctx.dir(), "user.email"))
+        });
+        context.register("organisation", |_, _| {
+            Config::current().organisation.clone()
+        });
+        // {{license}}, {{license:MIT OR Apache-2.0}}, {{license.id}}, {{license.notice}}
+        context.register("license", |_, token| {
+            let expr = match token.format {
+                Some(expr) => expr.to_string(),
+                None => Config::current().license.clone()?,
+            };
+            match token.arg {
+                None => license::identifier(&expr).ok(),
+                Some("id") => license::validate(&expr)
+                    .ok()
+                    .map(|expr| expr.as_ref().to_string()),
+                Some("notice") => license::notice(&expr).ok(),
+                Some(_) => None,
+            }
+        });
+        context.register("git", resolve_git);
+        context.register("env", |_, token| env::var(token.arg?).ok());
+        context
+    }
+
+    /// Add a resolver for `{{name...}}`, replacing any previous one with the same name
+    pub fn register<F>(&mut self, name: &str, resolver: F)
+    where
+        F: Fn(&Context, &Token) -> Option<String> + 'static,
+    {
+        self.resolvers.retain(|(key, _)| key != name);
+        self.resolvers.push((name.to_string(), Box::new(resolver)));
+    }
+
+    /// Target file, or the current directory to read its repository
+    pub fn dir(&self) -> &Path {
+        self.path.as_deref().unwrap_or(Path::new("."))
+    }
+
+    /// Git history of the target file, loaded once
+    pub fn history(&self) -> Option<&FileHistory> {
+        self.history
+            .get_or_init(|| git::file_history(self.path.as_ref()?))
+            .as_ref()
+            .filter(|history| !history.commits.is_empty())
+    }
+
+    pub fn resolve(&self, token: &Token) -> Option<String> {
+        self.resolvers
+            .iter()
+            .find(|(name, _)| name == token.name)
+            .and_then(|(_, resolver)| resolver(self, token))
+    }
+
+    /// Replace every known placeholder in `text`, unknown ones are kept as is
+    pub fn render(&self, text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find("{{") {
+            let Some(len) = rest[start..].find("}}") else {
+                break;
+            };
+            let end = start + len + 2;
+            result.push_str(&rest[..start]);
+            match self.resolve(&Token::parse(&rest[start + 2..end - 2])) {
+                Some(value) => result.push_str(&value),
+                None => result.push_str(&rest[start..end]),
+            }
+            rest = &rest[end..];
+        }
+        result.push_str(rest);
+        result
+    }
+}
+
+/// Environment variable overriding the current time, for reproducible output
+pub const NOW_ENV: &str = "DEV_HEADER_NOW";
+
+/// Local time, or the time set in `DEV_HEADER_NOW` (RFC 3339, `YYYY-MM-DD`
+/// or Unix timestamp) or `SOURCE_DATE_EPOCH`. A set time keeps the offset it
+/// was given with, UTC for dates and timestamps, so that the output doesn't
+/// depend on the time zone.
+pub fn now() -> DateTime<FixedOffset> {
+    env::var(NOW_ENV)
+        .ok()
+        .and_then(|value| parse_date(&value))
+        .or_else(|| {
+            env::var("SOURCE_DATE_EPOCH")
+                .ok()
+                .and_then(|value| value.trim().parse().ok())
+                .and_then(|secs| DateTime::from_timestamp(secs, 0))
+                .map(|date| date.fixed_offset())
+        })
+        .unwrap_or_else(|| Local::now().fixed_offset())
+}
+
+fn parse_date(value: &str) -> Option<DateTime<FixedOffset>> {
+    let value = value.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Some(date);
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Some(date.and_hms_opt(0, 0, 0)?.and_utc().fixed_offset());
+    }
+    DateTime::from_timestamp(value.parse().ok()?, 0).map(|date| date.fixed_offset())
+}
+
+/// strftime-style formatting in the offset of `date`, or in UTC, `None` on an
+/// invalid format string
+pub fn format_date(date: DateTime<FixedOffset>, utc: bool, format: &str) -> Option<String> {
+    let items = StrftimeItems::new(format).collect::<Vec<_>>();
+    if items.contains(&Item::Error) {
+        return None;
+    }
+    let result = if utc {
+        date.with_timezone(&Utc)
+            .format_with_items(items.into_iter())
+            .to_string()
+    } else {
+        date.format_with_items(items.into_iter()).to_string()
+    };
+    Some(result)
+}
+
+/// Commit dates are shown in the local time zone
+fn local(date: DateTime<FixedOffset>) -> DateTime<FixedOffset> {
+    date.with_timezone(&Local).fixed_offset()
+}
+
+/// `{{git.created}}`, `{{git.modified:%Y}}`, `{{git.contributors:; }}`...
+/// and any git config value such as `{{git.user.email}}`
+fn resolve_git(ctx: &Context, token: &Token) -> Option<String> {
+    let path = ctx.dir();
+    let date_format = token.format.unwrap_or("%Y-%m-%d");
+    // files without history are being created right now
+    match token.arg? {
+        "created" => match ctx.history().and_then(FileHistory::created) {
+            Some(commit) => format_date(local(commit.date), false, date_format),
+            None => format_date(now(), false, date_format),
+        },
+        "modified" => match ctx.history().and_then(FileHistory::modified) {
+            Some(commit) => format_date(local(commit.date), false, date_format),
+            None => format_date(now(), false, date_format),
+        },
+        "creator" => match ctx.history().and_then(FileHistory::created) {
+            Some(commit) => Some(commit.author.clone()),
+            None => ctx.resolve(&Token::parse("author")),
+        },
+        "contributors" => match ctx.history() {
+            Some(history) => Some(history.contributors().join(token.format.unwrap_or(", "))),
+            None => ctx.resolve(&Token::parse("author")),
+        },
+        "branch" => git::branch(path),
+        "remote" => git::remote_url(path, token.format.unwrap_or("origin")),
+        key => git::config(path, key),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_parts() {
+        assert_eq!(
+            Token::parse(" now.utc:%H:%M "),
+            Token {
+                name: "now",
+                arg: Some("utc"),
+                format: Some("%H:%M "),
+            }
+        );
+        assert_eq!(
+            Token::parse("git.user.email"),
+            Token {
+                name: "git",
+                arg: Some("user.email"),
+                format: None,
+            }
+        );
+        assert_eq!(
+            Token::parse("year"),
+            Token {
+                name: "year",
+                arg: None,
+                format: None,
+            }
+        );
+    }
+
+    #[test]
+    fn render_keeps_unknown_and_unclosed_tokens() {
+        let context = Context::new(Some(Path::new("src/main.rs")));
+        assert_eq!(
+            context.render("{{file_name}} in {{ file_path }}"),
+            "main.rs in src/main.rs"
+        );
+        assert_eq!(context.render("{{nope}} {{env}}"), "{{nope}} {{env}}");
+        assert_eq!(
+            context.render("{{file_stem}} {{file_name"),
+            "main {{file_name"
+        );
+        assert_eq!(context.render("}} {{ {{file_stem}}"), "}} {{ {{file_stem}}");
+    }
+
+    #[test]
+    fn registered_resolvers_replace_builtin_ones() {
+        let mut context = Context::new(None);
+        context.register("file_name", |_, token| {
+            Some(format!("custom.{}", token.arg.unwrap_or("rs")))
+        });
+        assert_eq!(context.render("{{file_name.py}}"), "custom.py");
+        // no file, the placeholder stays
+        assert_eq!(context.render("{{file_stem}}"), "{{file_stem}}");
+    }
+
+    #[test]
+    fn set_dates_keep_their_day() {
+        // no conversion to the local time zone
+        let date = parse_date("2025-03-04").unwrap();
+        assert_eq!(format_date(date, false, "%Y-%m-%d").unwrap(), "2025-03-04");
+        assert_eq!(format_date(date, true, "%d %b %Y").unwrap(), "04 Mar 2025");
+
+        let date = parse_date("2025-03-04T23:30:00-05:00").unwrap();
+        assert_eq!(
+            format_date(date, false, "%Y-%m-%d %H:%M").unwrap(),
+            "2025-03-04 23:30"
+        );
+        assert_eq!(
+            format_date(date, true, "%Y-%m-%d %H:%M").unwrap(),
+            "2025-03-05 04:30"
+        );
+
+        let date = parse_date("1741046400").unwrap();
+        assert_eq!(format_date(date, false, "%Y-%m-%d").unwrap(), "2025-03-04");
+        assert_eq!(parse_date("next tuesday"), None);
+    }
+
+    #[test]
+    fn invalid_format() {
+        let date = parse_date("2025-03-04").unwrap();
+        assert_eq!(format_date(date, false, "%Q"), None);
+    }
+}
@@ -0,0 +1,129 @@
+use std::path::{Component, Path, PathBuf};
+
+use globset::{Glob, GlobMatcher};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::comment::CommentStyle,
+    error::{Error, Result},
+};
+
+/// Pick a template and/or a comment style for the paths matching `glob`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathRule {
+    pub glob: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// Comment style name, see `CommentStyle::BUILTIN`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+/// A rule with its compiled glob
+pub struct Matcher {
+    pub rule: PathRule,
+    glob: GlobMatcher,
+    /// Globs of project files are relative to the project root
+    root: Option<PathBuf>,
+}
+
+impl PathRule {
+    /// Compile the glob, `root` is the directory relative paths are matched from
+    pub fn compile(self, root: Option<&Path>) -> Result<Matcher> {
+        if let Some(comment) = &self.comment
+            && CommentStyle::get(comment).is_none()
+        {
+            return Err(Error::Validation(format!(
+                "rule `{}`: unknown comment style `{}`",
+                self.glob, comment
+            )));
+        }
+        let glob = Glob::new(&self.glob)
+            .map_err(|e| Error::Validation(format!("rule `{}`: {}", self.glob, e)))?
+            .compile_matcher();
+        Ok(Matcher {
+            rule: self,
+            glob,
+            root: root.map(normalize),
+        })
+    }
+}
+
+impl Matcher {
+    /// `*.rs` matches in every directory, `src/**/*.rs` only below `src`. Globs of
+    /// project files are matched from the project root, wherever the command runs.
+    pub fn is_match(&self, path: &Path) -> bool {
+        match &self.root {
+            Some(root) => std::path::absolute(path).is_ok_and(|path| {
+                normalize(&path)
+                    .strip_prefix(root)
+                    .is_ok_and(|path| self.glob.is_match(path))
+            }),
+            None => self.glob.is_match(normalize(path)),
+        }
+    }
+}
+
+/// `path` without `.`, and with `..` removing the directory before it
+fn normalize(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(result.components().next_back(), Some(Component::Normal(_))) =>
+            {
+                result.pop();
+            }
+            component => result.push(component),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matcher(glob: &str, root: Option<&str>) -> Matcher {
+        let rule = PathRule {
+            glob: glob.to_string(),
+            template: None,
+            comment: None,
+        };
+        rule.compile(root.map(Path::new)).unwrap()
+    }
+
+    #[test]
+    fn normalize_resolves_dots() {
+        assert_eq!(normalize(Path::new("./a/./b/../c")), Path::new("a/c"));
+        assert_eq!(
+            normalize(Path::new("/p/src/../top.rs")),
+            Path::new("/p/top.rs")
+        );
+        assert_eq!(normalize(Path::new("../a/../../b")), Path::new("../../b"));
+    }
+
+    #[test]
+    fn project_globs_match_from_the_root() {
+        let rule = matcher("src/**/*.rs", Some("/p"));
+        assert!(rule.is_match(Path::new("/p/src/core/a.rs")));
+        assert!(!rule.is_match(Path::new("/p/src/../top.rs")));
+        assert!(!rule.is_match(Path::new("/other/src/a.rs")));
+
+        let rule = matcher("scripts/**", Some("/p"));
+        assert!(rule.is_match(Path::new("/p/src/../scripts/run")));
+    }
+
+    #[test]
+    fn config_globs_match_the_given_path() {
+        let rule = matcher("*.rs", None);
+        assert!(rule.is_match(Path::new("./src/main.rs")));
+        assert!(rule.is_match(Path::new("/p/src/main.rs")));
+        assert!(!rule.is_match(Path::new("main.py")));
+
+        let rule = matcher("scripts/**", None);
+        assert!(rule.is_match(Path::new("./scripts/run")));
+        assert!(rule.is_match(Path::new("src/../scripts/run")));
+    }
+}
@@ -0,0 +1,288 @@
+use std::path::PathBuf;
+
+use ignore::WalkBuilder;
+
+use crate::{
+    core::{
+        header::{build_header, find_header, render_header},
+        placeholder::Context,
+        template::Template,
+    },
+    error::{Error, Result},
+};
+
+const BOM: &str = "\u{feff}";
+
+/// Byte length of the lines that must stay above the header:
+/// BOM, shebang, `<?php`, XML prolog and Python encoding declaration
+pub fn prologue_len(text: &str) -> usize {
+    let mut end = 0;
+    if text.starts_with(BOM) {
+        end += BOM.len();
+    }
+
+    let next_line = |start: usize| -> usize {
+        match text[start..].find('\n') {
+            Some(idx) => start + idx + 1,
+            None => text.len(),
+        }
+    };
+
+    let rest = &text[end..];
+    if rest.starts_with("#!") || rest.starts_with("<?php") {
+        end = next_line(end);
+    } else if rest.starts_with("<?xml") {
+        end = match rest.find("?>") {
+            Some(idx) => next_line(end + idx),
+            None => next_line(end),
+        };
+    }
+
+    // PEP 263 encoding declaration
+    let rest = &text[end..];
+    if rest.starts_with('#')
+        && rest
+            .lines()
+            .next()
+            .is_some_and(|line| line.contains("coding:") || line.contains("coding="))
+    {
+        end = next_line(end);
+    }
+    end
+}
+
+fn line_ending(text: &str) -> &'static str {
+    if text.contains("\r\n") { "\r\n" } else { "\n" }
+}
+
+/// Whether `text` starts with a header generated from `template` or any of `known`,
+/// rendered with the comment style of `template`
+pub fn has_header(template: &Template, known: &[Template], text: &str) -> bool {
+    let body = &text[prologue_len(text)..];
+    find_header(template, body).is_some()
+        || known.iter().any(|other| {
+            let mut other = other.clone();
+            other.comment = template.comment.clone();
+            find_header(&other, body).is_some()
+        })
+}
+
+/// Render `template` and put it on top of `text`, below the prologue.
+/// Returns `None` when the file already starts with a header.
+pub fn insert_header(
+    template: &Template,
+    known: &[Template],
+    context: &Context,
+    text: &str,
+) -> Option<String> {
+    if has_header(template, known, text) {
+        return None;
+    }
+    let start = prologue_len(text);
+    let (prologue, body) = text.split_at(start);
+    let newline = line_ending(text);
+    let header = build_header(template, context);
+
+    let mut result = String::with_capacity(text.len() + header.len() + 4);
+    result.push_str(prologue);
+    if !prologue.is_empty() && !prologue.ends_with('\n') {
+        result.push_str(newline);
+    }
+    result.push_str(&header.replace('\n', newline));
+    result.push_str(newline);
+    if !body.is_empty() && !body.starts_with(['\r', '\n']) {
+        result.push_str(newline);
+    }
+    result.push_str(body);
+    Some(result)
+}
+
+/// Replace the header generated from `template` at the top of `text` with a fresh one,
+/// keeping everything else byte-for-byte and the years of its copyright notices.
+/// Returns `None` when no header is found.
+pub fn update_header(template: &Template, context: &Context, text: &str) -> Option<String> {
+    let start = prologue_len(text);
+    let len = find_header(template, &text[start..])?;
+    let old = &text[start..start + len];
+    let newline = line_ending(old);
+
+    let mut header = render_header(template, context, Some(old)).replace('\n', newline);
+    if old.ends_with('\n') {
+        header.push_str(newline);
+    }
+
+    let mut result = String::with_capacity(text.len() - old.len() + header.len());
+    result.push_str(&text[..start]);
+    result.push_str(&header);
+    result.push_str(&text[start + len..]);
+    Some(result)
+}
+
+#[derive(Debug, PartialEq)]
+pub enum HeaderStatus {
+    Ok,
+    Missing,
+    Stale,
+}
+
+pub fn header_status(template: &Template, context: &Context, text: &str) -> HeaderStatus {
+    match update_header(template, context, text) {
+        Some(result) if result == text => HeaderStatus::Ok,
+        Some(_) => HeaderStatus::Stale,
+        None => HeaderStatus::Missing,
+    }
+}
+
+/// Files under `paths`, honouring `.gitignore` and friends
+pub fn source_files(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let current = [PathBuf::from(".")];
+    let paths = if paths.is_empty() {
+        &current[..]
+    } else {
+        paths
+    };
+
+    let mut builder = WalkBuilder::new(&paths[0]);
+    builder.require_git(false);
+    for path in &paths[1..] {
+        builder.add(path);
+    }
+    let mut files = vec![];
+    for entry in builder.build() {
+        let entry = entry.map_err(|e| Error::Io(e.to_string()))?;
+        if entry
+            .file_type()
+            .is_some_and(|file_type| file_type.is_file())
+        {
+            files.push(entry.into_path());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn template() -> Template {
+        serde_json::from_value(json!({
+            "name": "test",
+            "length": 40,
+            "comment": "hash",
+            "border": null,
+            "fields": [{ "key": "File", "default_value": "a.py", "key_visible": true }],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn prologue_keeps_shebang_and_encoding() {
+        let text = "#!/usr/bin/env python\n# -*- coding: utf-8 -*-\nprint(1)\n";
+        assert_eq!(
+            &text[..prologue_len(text)],
+            "#!/usr/bin/env python\n# -*- coding: utf-8 -*-\n"
+        );
+    }
+
+    #[test]
+    fn prologue_keeps_bom_and_xml() {
+        let text = "\u{feff}<?xml version=\"1.0\"?>\n<root/>\n";
+        assert_eq!(&text[prologue_len(text)..], "<root/>\n");
+        assert_eq!(prologue_len("print(1)\n"), 0);
+    }
+
+    #[test]
+    fn inserts_above_ordinary_comments() {
+        let template = template();
+        let context = Context::new(None);
+        let text = "#!/bin/sh\n# keep this comment\necho hi\n";
+        let result = insert_header(&template, &[], &context, text).unwrap();
+        assert!(result.starts_with("#!/bin/sh\n# File : a.py"));
+        assert!(result.ends_with("\n\n# keep this comment\necho hi\n"));
+        assert_eq!(insert_header(&template, &[], &context, &result), None);
+    }
+
+    #[test]
+    fn update_keeps_the_rest_of_the_file() {
+        let template = template();
+        let context = Context::new(None);
+        let text = "# File : old.py\n\n# keep this comment\n";
+        let result = update_header(&template, &context, text).unwrap();
+        assert!(result.starts_with("# File : a.py"));
+        assert!(result.ends_with("\n\n# keep this comment\n"));
+        assert_eq!(
+            update_header(&template, &context, "# keep this comment\n"),
+            None
+        );
+    }
+
+    fn context(year: &'static str) -> Context {
+        let mut context = Context::new(None);
+        context.register("year", move |_, _| Some(year.to_string()));
+        context
+    }
+
+    #[test]
+    fn update_after_insert_with_hidden_keys() {
+        let template: Template = serde_json::from_value(json!({
+            "name": "test",
+            "length": 40,
+            "comment": "hash",
+            "border": null,
+            "fields": [
+                { "key": "File", "default_value": "a.py", "key_visible": true },
+                { "key": "C", "default_value": "Acme Corp", "kind": "copyright", "key_visible": false },
+            ],
+        }))
+        .unwrap();
+        let text = "# keep this comment
+print(1)
+";
+        let inserted = insert_header(&template, &[], &context("2020"), text).unwrap();
+        assert!(inserted.contains("\n# Copyright (c) 2020 Acme Corp"));
+
+        let updated = update_header(&template, &context("2025"), &inserted).unwrap();
+        assert_eq!(updated.matches("Copyright").count(), 1);
+        assert!(updated.contains("\n# Copyright (c) 2020-2025 Acme Corp"));
+        assert!(updated.ends_with("\n\n# keep this comment\nprint(1)\n"));
+        assert_eq!(
+            header_status(&template, &context("2025"), &updated),
+            HeaderStatus::Ok
+        );
+    }
+
+    #[test]
+    fn finds_headers_without_keys_by_their_static_lines() {
+        let template: Template = serde_json::from_value(json!({
+            "name": "nokeys",
+            "length": 40,
+            "comment": "hash",
+            "border": null,
+            "fields": [
+                { "key": "Project", "default_value": "My Project", "key_visible": false },
+                { "key": "File", "default_value": "{{file_name}}", "key_visible": false },
+            ],
+        }))
+        .unwrap();
+        let text = "# keep this comment
+print(1)
+";
+        let inserted = insert_header(&template, &[], &Context::new(None), text).unwrap();
+        assert_eq!(
+            insert_header(&template, &[], &Context::new(None), &inserted),
+            None
+        );
+
+        let context = Context::new(Some(Path::new("d.py")));
+        let updated = update_header(&template, &context, &inserted).unwrap();
+        assert!(updated.starts_with("# My Project"));
+        assert!(updated.contains("\n# d.py"));
+        assert!(updated.ends_with("\n\n# keep this comment\nprint(1)\n"));
+        assert_eq!(update_header(&template, &context, text), None);
+    }
+}
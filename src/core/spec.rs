@@ -0,0 +1,94 @@
+use std::{
+    fs,
+    io::{self, IsTerminal, Read},
+    path::Path,
+};
+
+use clap::ValueEnum;
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::error::{Error, Result};
+
+/// File format of specs and template libraries
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Format {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl Format {
+    /// Format named by the extension of `path`
+    pub fn from_path(path: &Path) -> Option<Format> {
+        match path.extension()?.to_str()? {
+            "json" => Some(Format::Json),
+            "toml" => Some(Format::Toml),
+            "yaml" | "yml" => Some(Format::Yaml),
+            _ => None,
+        }
+    }
+
+    pub fn parse<T: DeserializeOwned>(self, text: &str) -> Result<T> {
+        match self {
+            Format::Json => Ok(serde_json::from_str(text)?),
+            Format::Toml => toml::from_str(text).map_err(|e| Error::Parse(e.to_string())),
+            Format::Yaml => serde_yaml::from_str(text).map_err(|e| Error::Parse(e.to_string())),
+        }
+    }
+
+    pub fn write<T: Serialize>(self, value: &T) -> Result<String> {
+        match self {
+            Format::Json => Ok(serde_json::to_string_pretty(value)?),
+            Format::Toml => toml::to_string(value).map_err(|e| Error::Parse(e.to_string())),
+            Format::Yaml => serde_yaml::to_string(value).map_err(|e| Error::Parse(e.to_string())),
+        }
+    }
+}
+
+/// Whether prompts can be shown
+pub fn is_interactive() -> bool {
+    io::stdin().is_terminal()
+}
+
+/// Read a JSON, TOML or YAML spec from `path`, or from stdin when `path` is `-`.
+/// The format comes from the extension, otherwise JSON then TOML are tried.
+pub fn read_spec<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let text = if path == Path::new("-") {
+        let mut text = String::new();
+        io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|e| Error::Io(format!("stdin: {}", e)))?;
+        text
+    } else {
+        fs::read_to_string(path).map_err(|e| Error::io(path, e))?
+    };
+
+    match Format::from_path(path) {
+        Some(format) => format.parse(&text),
+        None => serde_json::from_str(&text).or_else(|json_err| {
+            toml::from_str(&text).map_err(|toml_err| {
+                Error::Parse(format!(
+                    "invalid JSON ({}) or TOML ({})",
+                    json_err, toml_err
+                ))
+            })
+        }),
+    }
+}
+
+/// Fail early instead of panicking in a prompt
+pub fn require_interactive(hint: &str) -> Result<()> {
+    if is_interactive() {
+        Ok(())
+    } else {
+        Err(Error::Prompt(format!("not a terminal, {}", hint)))
+    }
+}
+
+/// Parse `KEY=VALUE`
+pub fn parse_key_value(input: &str) -> std::result::Result<(String, String), String> {
+    match input.split_once('=') {
+        Some((key, value)) => Ok((key.trim().to_string(), value.to_string())),
+        None => Err(format!("invalid `{}`, expected KEY=VALUE", input)),
+    }
+}
@@ -1,94 +1,443 @@
-use std::{fs, path::PathBuf};
+use std::{
+    env, fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
 
+use chrono::Local;
+use console::style;
 use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
 
-use crate::core::template::Template;
+use crate::{
+    core::{
+        comment::CommentStyle,
+        config::Config,
+        rule::{Matcher, PathRule},
+        spec::read_spec,
+        template::Template,
+    },
+    error::{Error, Result},
+};
 
 const DEFAULT_DATA: &str = include_str!("../data/data.json");
 
+/// Layout version of `data.json`, add a step to `MIGRATIONS` when bumping it
+const DATA_VERSION: u64 = 1;
+
+/// Upgrades of `data.json`, step `i` turns version `i` into version `i + 1`
+const MIGRATIONS: [fn(Value) -> Result<Value>; DATA_VERSION as usize] = [wrap_templates];
+
+/// Checked-in project files, looked up from the current directory upwards
+const PROJECT_FILES: [&str; 2] = [".dev-header.toml", ".dev-header.json"];
+
+/// Content of a project file
+#[derive(Debug, Deserialize)]
+struct ProjectFile {
+    #[serde(default)]
+    templates: Vec<Template>,
+    #[serde(default)]
+    rules: Vec<PathRule>,
+}
+
+/// Templates written by `export`, shaped like a project file
+#[derive(Debug, Serialize)]
+pub struct Library {
+    pub templates: Vec<Template>,
+}
+
 pub struct Storage {
+    /// Project templates, then the user templates they don't shadow
     templates: Vec<Template>,
+    /// Templates of data.json, the only ones that are saved
+    user: Vec<Template>,
+    project: Vec<Template>,
+    project_file: Option<PathBuf>,
+    /// Rules of the project file, then of the config
+    rules: Vec<Matcher>,
     dir: PathBuf,
 }
 
 impl Storage {
-    pub fn new() -> Self {
-        if let Some(prj_dir) = ProjectDirs::from("com", "", "dev-header") {
-            let data_dir = prj_dir.data_dir().to_path_buf();
-            fs::create_dir_all(&data_dir).unwrap();
+    pub fn new() -> Result<Self> {
+        let data_dir = data_dir()?;
+        let data_file = data_dir.join("data.json");
+
+        if !data_file.exists() {
+            fs::write(&data_file, DEFAULT_DATA).map_err(|e| Error::io(&data_file, e))?;
+        }
+        let user = read_data(&data_file)?;
 
-            let data_file = data_dir.join("data.json");
+        let project_file = env::current_dir()
+            .ok()
+            .and_then(|dir| find_project_file(&dir));
+        let project = match &project_file {
+            Some(path) => read_spec::<ProjectFile>(path).map_err(|e| match e {
+                Error::Parse(e) => Error::Parse(format!("{}: {}", path.display(), e)),
+                e => e,
+            })?,
+            None => ProjectFile {
+                templates: vec![],
+                rules: vec![],
+            },
+        };
 
-            if !data_file.exists() {
-                fs::write(&data_file, DEFAULT_DATA).unwrap();
+        // skip broken rules, a warning is enough to fix them
+        let root = project_file.as_deref().and_then(Path::parent);
+        let project_rules = project.rules.into_iter().map(|rule| (rule, root));
+        let config_rules = Config::current()
+            .rules
+            .iter()
+            .cloned()
+            .map(|rule| (rule, None));
+        let mut rules = vec![];
+        for (rule, root) in project_rules.chain(config_rules) {
+            match rule.compile(root) {
+                Ok(matcher) => rules.push(matcher),
+                Err(e) => warn(&e.to_string()),
             }
-            let data_string = fs::read_to_string(&data_file).unwrap();
-            let templates = serde_json::from_str::<Vec<Template>>(&data_string).unwrap();
-            Self {
-                templates: templates,
-                dir: data_file,
+        }
+        let project = project.templates;
+
+        // keep broken templates loaded so they can still be fixed with `edit`
+        for template in user.iter().chain(&project) {
+            if let Err(e) = template.validate() {
+                warn(&format!("template `{}`: {}", template.name, e));
+            }
+        }
+        let mut storage = Self {
+            templates: vec![],
+            user,
+            project,
+            project_file,
+            rules,
+            dir: data_file,
+        };
+        storage.merge();
+        Ok(storage)
+    }
+
+    /// `user` templates saved to `data_file`, and `project` templates of a project file
+    #[cfg(test)]
+    pub(crate) fn with_templates(
+        user: Vec<Template>,
+        project: Vec<Template>,
+        data_file: PathBuf,
+    ) -> Self {
+        let mut storage = Self {
+            templates: vec![],
+            user,
+            project,
+            project_file: Some(PathBuf::from(PROJECT_FILES[0])),
+            rules: vec![],
+            dir: data_file,
+        };
+        storage.merge();
+        storage
+    }
+
+    /// Project templates win over user templates with the same name
+    fn merge(&mut self) {
+        self.templates = self.project.clone();
+        for template in &self.user {
+            if !self.is_project(&template.name) {
+                self.templates.push(template.clone());
             }
-        } else {
-            panic!("Could not determine user directory");
         }
     }
 
-    pub fn add(&mut self, template: Template) -> Result<(), Box<dyn std::error::Error>> {
-        self.templates.push(template);
+    /// Whether `name` comes from the project file
+    pub fn is_project(&self, name: &str) -> bool {
+        self.project.iter().any(|template| template.name == name)
+    }
+
+    /// Project templates can only be changed in their file
+    pub(crate) fn check_user(&self, name: &str) -> Result<()> {
+        match &self.project_file {
+            Some(path) if self.is_project(name) => Err(Error::Validation(format!(
+                "`{}` is defined in {}, edit that file instead",
+                name,
+                path.display()
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn add(&mut self, template: Template) -> Result<()> {
+        self.user.push(template);
         self.save()
     }
 
-    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
-        fs::write(&self.dir, serde_json::json!(self.templates).to_string())?;
+    /// Add `template`, replacing the user template with the same name but not its
+    /// default flag, call `save` afterwards
+    pub fn put(&mut self, template: Template) -> Result<()> {
+        self.check_user(&template.name)?;
+        match self.user.iter_mut().find(|user| user.name == template.name) {
+            Some(existing) => {
+                *existing = Template {
+                    default: existing.default,
+                    ..template
+                }
+            }
+            None => self.user.push(template),
+        }
         Ok(())
     }
 
+    pub fn save(&mut self) -> Result<()> {
+        self.merge();
+        let data = json!({ "version": DATA_VERSION, "templates": self.user });
+        write_atomic(&self.dir, &data.to_string())
+    }
+
     pub fn get(&self, name: &str) -> Option<&Template> {
         self.templates.iter().find(|template| template.name == name)
     }
 
-    pub fn get_mut(&mut self, name: &str) -> Option<&mut Template> {
-        self.templates
+    /// User template to change, call `save` afterwards
+    pub fn get_mut(&mut self, name: &str) -> Result<&mut Template> {
+        self.check_user(name)?;
+        self.user
             .iter_mut()
             .find(|template| template.name == name)
+            .ok_or_else(|| Error::not_found(name))
     }
 
     pub fn get_default(&self) -> Option<&Template> {
-        self.templates
+        self.templates.iter().find(|template| template.default)
+    }
+
+    /// Template named `name`, else the one of the first matching rule, else the one
+    /// the config maps to the extension of `path`, else the default one
+    pub fn template_for(&self, name: Option<&str>, path: &Path) -> Result<&Template> {
+        let name = name
+            .or_else(|| {
+                self.rules
+                    .iter()
+                    .filter(|matcher| matcher.rule.template.is_some())
+                    .find(|matcher| matcher.is_match(path))
+                    .and_then(|matcher| matcher.rule.template.as_deref())
+            })
+            .or_else(|| Config::current().template_for(path));
+        match name {
+            Some(name) => self.get(name).ok_or_else(|| Error::not_found(name)),
+            None => self
+                .get_default()
+                .ok_or_else(|| Error::NotFound("default header".to_string())),
+        }
+    }
+
+    /// Comment style of the first matching rule with one, else from the file extension
+    pub fn comment_for(&self, path: &Path) -> Option<String> {
+        self.rules
             .iter()
-            .find(|template| template.default == true)
+            .filter(|matcher| matcher.rule.comment.is_some())
+            .find(|matcher| matcher.is_match(path))
+            .and_then(|matcher| matcher.rule.comment.clone())
+            .or_else(|| CommentStyle::from_path(path).map(|style| style.name.to_string()))
     }
 
     pub fn list(&self) -> &Vec<Template> {
         &self.templates
     }
 
-    pub fn delete(&mut self, name: &str) -> Result<(), String> {
-        if let Some(idx) = self
-            .templates
-            .iter()
-            .position(|template| template.name == name)
-        {
-            let deleted = self.templates.remove(idx);
-            if deleted.default && self.templates.len() > 0 {
-                self.templates[0].default = true;
-            }
-            if let Err(e) = self.save() {
-                Err(e.to_string())
-            } else {
-                Ok(())
-            }
-        } else {
-            Err("not found".to_string())
+    pub fn delete(&mut self, name: &str) -> Result<()> {
+        self.check_user(name)?;
+        let Some(idx) = self.user.iter().position(|template| template.name == name) else {
+            return Err(Error::not_found(name));
+        };
+        let deleted = self.user.remove(idx);
+        if deleted.default && !self.user.is_empty() {
+            self.user[0].default = true;
         }
+        self.save()
     }
 
-    pub fn delete_all(&mut self) -> Result<(), String> {
-        self.templates = vec![];
-        if let Err(e) = self.save() {
-            Err(e.to_string())
-        } else {
-            Ok(())
-        }
+    /// Delete all user templates, project templates stay
+    pub fn delete_all(&mut self) -> Result<()> {
+        self.user = vec![];
+        self.save()
+    }
+}
+
+/// Directory of `data.json` and `config.toml`, created when missing
+pub fn data_dir() -> Result<PathBuf> {
+    let Some(prj_dir) = ProjectDirs::from("com", "", "dev-header") else {
+        return Err(Error::Io("Could not determine user directory".to_string()));
+    };
+    let data_dir = prj_dir.data_dir().to_path_buf();
+    fs::create_dir_all(&data_dir).map_err(|e| Error::io(&data_dir, e))?;
+    Ok(data_dir)
+}
+
+/// User templates of `data.json`, upgrading the file first when it is older than
+/// `DATA_VERSION`. The old file is kept as `data.json.v<version>-<timestamp>.bak`.
+fn read_data(path: &Path) -> Result<Vec<Template>> {
+    let parse_error = |e: serde_json::Error| Error::Parse(format!("{}: {}", path.display(), e));
+    let text = fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+    let data: Value = serde_json::from_str(&text).map_err(parse_error)?;
+
+    let (version, mut data) = migrate(data, path)?;
+    if version < DATA_VERSION {
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(format!(
+            ".v{}-{}.bak",
+            version,
+            Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        let backup = PathBuf::from(backup);
+        // never replace an earlier backup
+        fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&backup)
+            .and_then(|mut file| file.write_all(text.as_bytes()))
+            .map_err(|e| Error::io(&backup, e))?;
+
+        write_atomic(path, &data.to_string())?;
+        eprintln!(
+            "{} {} to version {}, backup in {}",
+            style("Migrated").green().bold(),
+            path.display(),
+            DATA_VERSION,
+            backup.display()
+        );
+    }
+
+    let templates = data
+        .get_mut("templates")
+        .map(Value::take)
+        .ok_or_else(|| Error::Parse(format!("{}: missing `templates`", path.display())))?;
+    serde_json::from_value(templates).map_err(parse_error)
+}
+
+/// `data` of `path` upgraded to `DATA_VERSION`, and the version it had
+fn migrate(mut data: Value, path: &Path) -> Result<(u64, Value)> {
+    // version 0 is the bare list of templates
+    let version = match &data {
+        Value::Array(_) => 0,
+        data => data
+            .get("version")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| Error::Parse(format!("{}: missing `version`", path.display())))?,
+    };
+    if version > DATA_VERSION {
+        return Err(Error::Parse(format!(
+            "{}: version {} is newer than this build supports ({}), upgrade dev-header",
+            path.display(),
+            version,
+            DATA_VERSION
+        )));
+    }
+    for step in &MIGRATIONS[version as usize..] {
+        data = step(data)?;
+    }
+    Ok((version, data))
+}
+
+/// Write through a temporary file next to `path`, so an interrupted write
+/// leaves the old file in place
+fn write_atomic(path: &Path, text: &str) -> Result<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    fs::write(&temp, text).map_err(|e| Error::io(&temp, e))?;
+    fs::rename(&temp, path).map_err(|e| Error::io(path, e))
+}
+
+/// Version 0 to 1: the list of templates moves into `{ "version", "templates" }`
+fn wrap_templates(data: Value) -> Result<Value> {
+    Ok(json!({ "version": 1, "templates": data }))
+}
+
+/// `.dev-header.toml` or `.dev-header.json` in `dir` or its closest parent
+fn find_project_file(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .flat_map(|dir| PROJECT_FILES.map(|name| dir.join(name)))
+        .find(|path| path.is_file())
+}
+
+fn warn(message: &str) {
+    eprintln!("{}: {}", style("warning").yellow().bold(), message);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process;
+
+    use super::*;
+
+    fn named(name: &str, value: &str) -> Template {
+        serde_json::from_value(json!({
+            "name": name,
+            "border": null,
+            "fields": [{ "key": "File", "default_value": value }],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn finds_the_closest_project_file() {
+        let root = env::temp_dir().join(format!("dev-header-project-{}", process::id()));
+        let dir = root.join("a").join("b");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(root.join(".dev-header.json"), "{}").unwrap();
+        let found = find_project_file(&dir);
+        fs::write(root.join("a").join(".dev-header.json"), "{}").unwrap();
+        fs::write(root.join("a").join(".dev-header.toml"), "").unwrap();
+        let closest = find_project_file(&dir);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(found, Some(root.join(".dev-header.json")));
+        // toml is looked up first
+        assert_eq!(closest, Some(root.join("a").join(".dev-header.toml")));
+    }
+
+    #[test]
+    fn project_templates_shadow_user_ones() {
+        let mut store = Storage::with_templates(
+            vec![named("shared", "user"), named("mine", "user")],
+            vec![named("shared", "project")],
+            PathBuf::from("data.json"),
+        );
+        let names: Vec<&str> = store.list().iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["shared", "mine"]);
+        assert_eq!(
+            store.get("shared").unwrap().fields[0].default_value,
+            "project"
+        );
+        assert!(store.is_project("shared"));
+        assert!(matches!(store.get_mut("shared"), Err(Error::Validation(_))));
+        assert!(store.get_mut("mine").is_ok());
+    }
+
+    #[test]
+    fn migrates_bare_list() {
+        let path = Path::new("data.json");
+        let (version, data) = migrate(json!([{ "name": "a" }]), path).unwrap();
+        assert_eq!(version, 0);
+        assert_eq!(
+            data,
+            json!({ "version": DATA_VERSION, "templates": [{ "name": "a" }] })
+        );
+    }
+
+    #[test]
+    fn keeps_current_version() {
+        let path = Path::new("data.json");
+        let current = json!({ "version": DATA_VERSION, "templates": [] });
+        let (version, data) = migrate(current.clone(), path).unwrap();
+        assert_eq!(version, DATA_VERSION);
+        assert_eq!(data, current);
+    }
+
+    #[test]
+    fn rejects_newer_or_unversioned() {
+        let path = Path::new("data.json");
+        let newer = json!({ "version": DATA_VERSION + 1, "templates": [] });
+        assert!(matches!(migrate(newer, path), Err(Error::Parse(_))));
+        let unversioned = json!({ "templates": [] });
+        assert!(matches!(migrate(unversioned, path), Err(Error::Parse(_))));
     }
 }
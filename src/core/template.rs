@@ -1,9 +1,16 @@
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    core::{comment::CommentStyle, header::header_frame, text},
+    error::{Error, Result},
+};
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Template {
     pub name: String,
+    #[serde(default)]
     pub default: bool,
+    #[serde(default = "Template::default_length")]
     pub length: u16,
     pub border: Option<Border>,
     pub fields: Vec<Field>,
@@ -11,20 +18,335 @@ pub struct Template {
     pub space_before: u8,
     #[serde(default)]
     pub space_after: u8,
+    /// Comment style name, see `CommentStyle::BUILTIN`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// Drawn between a visible key and its value
+    #[serde(default = "Template::default_separator")]
+    pub separator: String,
+    /// Fixed width of the key column, otherwise the widest key of each alignment group
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_width: Option<u8>,
 }
 
 impl Template {
     pub const DEFAULT_HEADER_LEN: u16 = 75;
     pub const MAX_HEADER_LEN: u16 = 255;
     pub const SPACE_HEIGHT: u8 = 0;
+    pub const MAX_SPACE_HEIGHT: u8 = 10;
+    pub const DEFAULT_SEPARATOR: &str = " : ";
+
+    fn default_length() -> u16 {
+        Self::DEFAULT_HEADER_LEN
+    }
+
+    fn default_separator() -> String {
+        Self::DEFAULT_SEPARATOR.to_string()
+    }
+
+    /// Check values that can't be enforced by prompts, e.g. for specs read from files
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(Error::Validation(
+                "Header name must not be empty".to_string(),
+            ));
+        }
+        if self.length == 0 || self.length > Self::MAX_HEADER_LEN {
+            return Err(Error::Validation(format!(
+                "Header length must greater than 0 and less than or equal to {}",
+                Self::MAX_HEADER_LEN
+            )));
+        }
+        if self.space_before > Self::MAX_SPACE_HEIGHT || self.space_after > Self::MAX_SPACE_HEIGHT {
+            return Err(Error::Validation(format!(
+                "Spacing must be less than or equal to {}",
+                Self::MAX_SPACE_HEIGHT
+            )));
+        }
+        if let Some(comment) = &self.comment
+            && CommentStyle::get(comment).is_none()
+        {
+            return Err(Error::Validation(format!(
+                "unknown comment style `{}`",
+                comment
+            )));
+        }
+        if let Some(border) = &self.border
+            && border.parts().iter().any(|part| {
+                part.as_deref()
+                    .is_some_and(|s| s.contains(char::is_control))
+            })
+        {
+            return Err(Error::Validation(
+                "Border must not contain line breaks or control characters".to_string(),
+            ));
+        }
+        if self.separator.contains(char::is_control) {
+            return Err(Error::Validation(
+                "Separator must not contain line breaks or control characters".to_string(),
+            ));
+        }
+        if self.key_width == Some(0) {
+            return Err(Error::Validation(
+                "Key width must be greater than 0".to_string(),
+            ));
+        }
+        if let Some(key_width) = self.key_width
+            && let Some(field) = self
+                .value_fields()
+                .find(|field| field.key_visible && text::width(&field.key) > key_width as usize)
+        {
+            return Err(Error::Validation(format!(
+                "key `{}` is wider than the key width ({})",
+                field.key, key_width
+            )));
+        }
+        if let Some(field) = self
+            .fields
+            .iter()
+            .find(|field| field.padding() >= self.length as usize)
+        {
+            return Err(Error::Validation(format!(
+                "Field `{}`: padding must be less than the header length ({})",
+                field.label(),
+                self.length
+            )));
+        }
+        self.check_layout().map_err(Error::Validation)?;
+        for field in &self.fields {
+            if text::width(&field.key) > (self.length / 2) as usize {
+                return Err(Error::Validation(format!(
+                    "Key `{}`: the length of the key must be less than half of the total length",
+                    field.key
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Columns of body lines taken by the comment prefix and the left and right border
+    pub fn frame_width(&self) -> usize {
+        let (left, right) = self
+            .border
+            .as_ref()
+            .map(Border::side_widths)
+            .unwrap_or_default();
+        text::width(&header_frame(self).prefix) + left + right
+    }
+
+    /// Columns between the left and right border, so that body lines are `length` wide
+    pub fn body_len(&self) -> usize {
+        (self.length as usize).saturating_sub(self.frame_width())
+    }
+
+    /// Columns of the key column of `field`: `key_width` when set,
+    /// otherwise the widest visible key of the same alignment group
+    pub fn key_len(&self, field: &Field) -> usize {
+        if let Some(key_width) = self.key_width {
+            return key_width as usize;
+        }
+        self.value_fields()
+            .filter(|other| other.key_visible && other.group == field.group)
+            .map(|other| text::width(&other.key))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Columns of a key line of `field` besides the value: ' ', the key column and the separator
+    pub fn key_column(&self, field: &Field) -> usize {
+        1 + self.key_len(field) + text::width(&self.separator)
+    }
+
+    /// Check that every line fits into `length`, run before rendering
+    pub fn check_layout(&self) -> std::result::Result<(), String> {
+        let available = self.body_len();
+        if available == 0 {
+            return Err(format!(
+                "length {} leaves no room for the header body, the comment and borders take {} columns",
+                self.length,
+                self.frame_width()
+            ));
+        }
+        for field in &self.fields {
+            // at least one column is left for the value, after the leading space
+            let mut needed = if field.key_visible && !field.kind.is_separator() {
+                field.padding() + self.key_column(field) + 1
+            } else {
+                field.padding() + 2
+            };
+            if field.kind == FieldKind::List && field.list_style == ListStyle::Bullet {
+                needed += text::width(Field::BULLET);
+            }
+            if needed > available {
+                let what = match field.kind.is_separator() {
+                    true => field.kind.name().to_string(),
+                    false => format!("key `{}`", field.key),
+                };
+                return Err(format!(
+                    "{} needs {} columns but only {} are available",
+                    what, needed, available
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Set field values by key, returns the keys that were set
+    pub fn set_values(&mut self, values: Vec<(String, String)>) -> Result<Vec<String>> {
+        let mut supplied = vec![];
+        let mut unknown = vec![];
+        for (key, value) in values {
+            match self
+                .fields
+                .iter_mut()
+                .find(|field| field.key == key && !field.kind.is_separator())
+            {
+                Some(field) => {
+                    field.default_value = value;
+                    supplied.push(key);
+                }
+                None => unknown.push(format!("`{}`", key)),
+            }
+        }
+        if unknown.is_empty() {
+            Ok(supplied)
+        } else {
+            Err(Error::Validation(format!(
+                "unknown field {} (available: {})",
+                unknown.join(", "),
+                self.keys().join(", ")
+            )))
+        }
+    }
+
+    /// Keys of required fields not in `supplied`
+    pub fn missing_required(&self, supplied: &[String]) -> Vec<&str> {
+        self.value_fields()
+            .filter(|field| field.required && !supplied.contains(&field.key))
+            .map(|field| field.key.as_str())
+            .collect()
+    }
+
+    pub fn keys(&self) -> Vec<&str> {
+        self.value_fields()
+            .map(|field| field.key.as_str())
+            .collect()
+    }
+
+    /// Fields that take a value, without separators
+    pub fn value_fields(&self) -> impl Iterator<Item = &Field> {
+        self.fields
+            .iter()
+            .filter(|field| !field.kind.is_separator())
+    }
+
+    pub fn comment_style(&self) -> &'static CommentStyle {
+        self.comment
+            .as_deref()
+            .and_then(CommentStyle::get)
+            .unwrap_or_else(CommentStyle::default_style)
+    }
+
+    /// Override the comment style from `--lang`/`--style` options
+    pub fn set_comment(&mut self, lang: Option<&str>, style: Option<&str>) -> Result<()> {
+        if let Some(name) = CommentStyle::resolve(lang, style)? {
+            self.comment = Some(name);
+        }
+        Ok(())
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Sides are repeated to fill the line, corners are drawn once
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Border {
-    pub left: Option<char>,
-    pub top: Option<char>,
-    pub right: Option<char>,
-    pub bottom: Option<char>,
+    pub left: Option<String>,
+    pub top: Option<String>,
+    pub right: Option<String>,
+    pub bottom: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_left: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_right: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bottom_left: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bottom_right: Option<String>,
+}
+
+impl Border {
+    /// Box-drawing presets: name, then sides (horizontal, vertical) and corners
+    /// (top left, top right, bottom left, bottom right)
+    const PRESETS: [(&'static str, [&'static str; 6]); 4] = [
+        ("single", ["─", "│", "┌", "┐", "└", "┘"]),
+        ("double", ["═", "║", "╔", "╗", "╚", "╝"]),
+        ("rounded", ["─", "│", "╭", "╮", "╰", "╯"]),
+        ("heavy", ["━", "┃", "┏", "┓", "┗", "┛"]),
+    ];
+
+    pub fn preset_names() -> Vec<&'static str> {
+        Self::PRESETS.iter().map(|(name, _)| *name).collect()
+    }
+
+    pub fn preset(name: &str) -> Option<Border> {
+        let (
+            _,
+            [
+                horizontal,
+                vertical,
+                top_left,
+                top_right,
+                bottom_left,
+                bottom_right,
+            ],
+        ) = Self::PRESETS.iter().find(|(preset, _)| *preset == name)?;
+        let some = |s: &str| Some(s.to_string());
+        Some(Border {
+            left: some(vertical),
+            top: some(horizontal),
+            right: some(vertical),
+            bottom: some(horizontal),
+            top_left: some(top_left),
+            top_right: some(top_right),
+            bottom_left: some(bottom_left),
+            bottom_right: some(bottom_right),
+        })
+    }
+
+    /// Like `preset`, with the available names in the error
+    pub fn from_preset(name: &str) -> Result<Border> {
+        Self::preset(name).ok_or_else(|| {
+            Error::Validation(format!(
+                "unknown border preset `{}` (available: {})",
+                name,
+                Self::preset_names().join(", ")
+            ))
+        })
+    }
+
+    fn parts(&self) -> [&Option<String>; 8] {
+        [
+            &self.left,
+            &self.top,
+            &self.right,
+            &self.bottom,
+            &self.top_left,
+            &self.top_right,
+            &self.bottom_left,
+            &self.bottom_right,
+        ]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parts()
+            .iter()
+            .all(|part| part.as_deref().unwrap_or_default().is_empty())
+    }
+
+    /// Columns taken by the left and right side on every body line
+    pub fn side_widths(&self) -> (usize, usize) {
+        let side = |part: &Option<String>| text::width(part.as_deref().unwrap_or_default());
+        (side(&self.left), side(&self.right))
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -35,17 +357,313 @@ pub struct Field {
     pub align: Alignment,
     #[serde(default)]
     pub key_visible: bool,
+    /// Must be given a value by `apply` and `default`
+    #[serde(default)]
+    pub required: bool,
+    /// Blank columns before the field, inside the border
+    #[serde(default)]
+    pub padding_left: u8,
+    /// Blank columns after the field, inside the border
+    #[serde(default)]
+    pub padding_right: u8,
+    #[serde(default)]
+    pub kind: FieldKind,
+    /// How `FieldKind::List` values are drawn
+    #[serde(default)]
+    pub list_style: ListStyle,
+    /// Year a `FieldKind::Copyright` notice is extended to
+    #[serde(default, skip_serializing_if = "YearSource::is_now")]
+    pub copyright_year: YearSource,
+    /// Fields of the same alignment group share the width of their key column
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// How the value of a field is laid out
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldKind {
+    /// Whitespace is collapsed and the text is wrapped
+    #[default]
+    Text,
+    /// Line breaks and blank lines are kept, each line is wrapped
+    Multiline,
+    /// One item per line, or comma separated when on a single line
+    List,
+    /// `Copyright (c) <years> <value>`, the years of an existing header are kept
+    Copyright,
+    /// Full-width line repeating the default value, `-` when empty
+    Rule,
+    /// Empty line
+    Blank,
+    /// Sub-heading showing the default value, never prompted
+    Heading,
+}
+
+impl FieldKind {
+    /// Kinds of fields that take a value
+    pub const ALL: [FieldKind; 4] = [
+        FieldKind::Text,
+        FieldKind::Multiline,
+        FieldKind::List,
+        FieldKind::Copyright,
+    ];
+    pub const SEPARATORS: [FieldKind; 3] = [FieldKind::Rule, FieldKind::Blank, FieldKind::Heading];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FieldKind::Text => "text",
+            FieldKind::Multiline => "multiline",
+            FieldKind::List => "list",
+            FieldKind::Copyright => "copyright",
+            FieldKind::Rule => "rule",
+            FieldKind::Blank => "blank",
+            FieldKind::Heading => "heading",
+        }
+    }
+
+    /// Drawn between fields, has no key and takes no value
+    pub fn is_separator(&self) -> bool {
+        FieldKind::SEPARATORS.contains(self)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ListStyle {
+    /// One bulleted item per line
+    #[default]
+    Bullet,
+    /// Items joined with ", "
+    Comma,
+}
+
+impl ListStyle {
+    pub const ALL: [ListStyle; 2] = [ListStyle::Bullet, ListStyle::Comma];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ListStyle::Bullet => "bullet",
+            ListStyle::Comma => "comma",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum YearSource {
+    /// Current year
+    #[default]
+    Now,
+    /// Year of the last commit of the file, the current year without history
+    Git,
+}
+
+impl YearSource {
+    pub const ALL: [YearSource; 2] = [YearSource::Now, YearSource::Git];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            YearSource::Now => "now",
+            YearSource::Git => "git",
+        }
+    }
+
+    fn is_now(&self) -> bool {
+        *self == YearSource::Now
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum Alignment {
     Left,
     Center,
+    Right,
+    /// Spread words over the whole line, except on the last line
+    Justify,
+}
+
+impl Alignment {
+    pub const ALL: [Alignment; 4] = [
+        Alignment::Left,
+        Alignment::Center,
+        Alignment::Right,
+        Alignment::Justify,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Alignment::Left => "left",
+            Alignment::Center => "center",
+            Alignment::Right => "right",
+            Alignment::Justify => "justify",
+        }
+    }
 }
 
 impl Field {
+    /// Drawn before each item of a bulleted list
+    pub const BULLET: &str = "- ";
+
+    /// Left aligned field of `kind` without a key, the other options left unset
+    pub fn new(kind: FieldKind, value: String) -> Self {
+        Field {
+            key: String::new(),
+            default_value: value,
+            align: Self::default_align(),
+            key_visible: false,
+            required: false,
+            padding_left: 0,
+            padding_right: 0,
+            kind,
+            list_style: ListStyle::default(),
+            copyright_year: YearSource::default(),
+            group: None,
+        }
+    }
+
     fn default_align() -> Alignment {
         Alignment::Left
     }
+
+    pub fn padding(&self) -> usize {
+        self.padding_left as usize + self.padding_right as usize
+    }
+
+    /// Name shown in prompts and messages, separators have no key
+    pub fn label(&self) -> &str {
+        match self.kind {
+            FieldKind::Rule | FieldKind::Blank => self.kind.name(),
+            FieldKind::Heading => &self.default_value,
+            _ => &self.key,
+        }
+    }
+
+    /// Items of a list value: one per line, or comma separated on a single line
+    pub fn items(value: &str) -> Vec<&str> {
+        let items: Vec<&str> = if value.contains('\n') {
+            value.lines().collect()
+        } else {
+            value.split(',').collect()
+        };
+        items
+            .into_iter()
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn template(fields: serde_json::Value) -> Template {
+        serde_json::from_value(json!({
+            "name": "test",
+            "length": 60,
+            "border": null,
+            "fields": fields,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn padding_does_not_overflow() {
+        let template = template(json!([
+            { "key": "a", "default_value": "x", "padding_left": 200, "padding_right": 100 }
+        ]));
+        assert_eq!(template.fields[0].padding(), 300);
+        assert!(matches!(template.validate(), Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn padding_must_leave_room_for_the_value() {
+        let template = template(json!([
+            { "key": "a", "default_value": "x", "padding_left": 2, "padding_right": 2 }
+        ]));
+        assert!(template.validate().is_ok());
+    }
+
+    #[test]
+    fn set_values_by_key() {
+        let mut template = template(json!([
+            { "key": "File", "default_value": "", "required": true },
+            { "key": "", "default_value": "", "kind": "rule" },
+            { "key": "Author", "default_value": "me", "required": true },
+            { "key": "Notes", "default_value": "" },
+        ]));
+        let supplied = template
+            .set_values(vec![("File".to_string(), "a.rs".to_string())])
+            .unwrap();
+        assert_eq!(supplied, ["File"]);
+        assert_eq!(template.fields[0].default_value, "a.rs");
+        assert_eq!(template.missing_required(&supplied), ["Author"]);
+
+        let error = template
+            .set_values(vec![("Nope".to_string(), "x".to_string())])
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unknown field `Nope` (available: File, Author, Notes)"
+        );
+        // separators take no value
+        assert!(
+            template
+                .set_values(vec![(String::new(), "x".to_string())])
+                .is_err()
+        );
+    }
+
+    fn sized(length: u16, fields: serde_json::Value) -> Template {
+        Template {
+            length,
+            comment: Some("hash".to_string()),
+            ..template(fields)
+        }
+    }
+
+    #[test]
+    fn check_layout_names_the_field_that_does_not_fit() {
+        let fields = json!([
+            { "key": "Description", "default_value": "x", "key_visible": true },
+        ]);
+        assert_eq!(sized(17, fields.clone()).check_layout(), Ok(()));
+        assert_eq!(
+            sized(16, fields).check_layout().unwrap_err(),
+            "key `Description` needs 16 columns but only 15 are available"
+        );
+
+        let fields = json!([{ "key": "", "default_value": "Title", "kind": "heading" }]);
+        assert_eq!(
+            sized(2, fields.clone()).check_layout().unwrap_err(),
+            "heading needs 2 columns but only 1 are available"
+        );
+        assert_eq!(
+            sized(1, fields).check_layout().unwrap_err(),
+            "length 1 leaves no room for the header body, the comment and borders take 1 columns"
+        );
+    }
+
+    #[test]
+    fn key_len_per_group_or_fixed() {
+        let mut template = template(json!([
+            { "key": "File", "default_value": "", "key_visible": true },
+            { "key": "Description", "default_value": "", "key_visible": true },
+            { "key": "Hidden key here", "default_value": "", "key_visible": false },
+            { "key": "Id", "default_value": "", "key_visible": true, "group": "meta" },
+            { "key": "Owner", "default_value": "", "key_visible": true, "group": "meta" },
+        ]));
+        let len = |template: &Template, idx: usize| template.key_len(&template.fields[idx]);
+        assert_eq!(len(&template, 0), 11);
+        assert_eq!(len(&template, 3), 5);
+        assert_eq!(template.key_column(&template.fields[3]), 1 + 5 + 3);
+
+        template.key_width = Some(8);
+        assert_eq!(len(&template, 0), 8);
+        assert_eq!(len(&template, 4), 8);
+    }
 }
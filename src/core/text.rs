@@ -0,0 +1,189 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::core::template::Alignment;
+
+/// Terminal columns taken by `text`, wide characters count twice
+pub fn width(text: &str) -> usize {
+    text.width()
+}
+
+/// Pad `text` with spaces to exactly `len` columns, `Justify` pads like `Left`
+pub fn pad(text: &str, len: usize, align: &Alignment) -> String {
+    let fill = len.saturating_sub(width(text));
+    let (left, right) = match align {
+        Alignment::Left | Alignment::Justify => (0, fill),
+        Alignment::Center => (fill / 2, fill - fill / 2),
+        Alignment::Right => (fill, 0),
+    };
+    format!("{}{}{}", " ".repeat(left), text, " ".repeat(right))
+}
+
+/// `pattern` repeated to exactly `len` columns, cut between grapheme clusters
+pub fn repeat(pattern: &str, len: usize) -> String {
+    let mut result = String::new();
+    let mut result_width = 0;
+    for grapheme in pattern.graphemes(true).cycle() {
+        let grapheme_width = width(grapheme);
+        // zero width patterns would never fill the line
+        if grapheme_width == 0 || result_width + grapheme_width > len {
+            break;
+        }
+        result.push_str(grapheme);
+        result_width += grapheme_width;
+    }
+    result.push_str(&" ".repeat(len - result_width));
+    result
+}
+
+/// Spread the spaces between the words of `text` so it is exactly `len` columns
+pub fn justify(text: &str, len: usize) -> String {
+    let words: Vec<&str> = text.split(' ').collect();
+    let gaps = words.len() - 1;
+    let fill = len.saturating_sub(width(text));
+    if gaps == 0 || fill == 0 {
+        return pad(text, len, &Alignment::Left);
+    }
+    let mut result = String::from(words[0]);
+    for (i, word) in words[1..].iter().enumerate() {
+        // leftmost gaps take the remainder
+        let spaces = 1 + fill / gaps + usize::from(i < fill % gaps);
+        result.push_str(&" ".repeat(spaces));
+        result.push_str(word);
+    }
+    result
+}
+
+/// Pad every wrapped line to `len` columns, the last line of justified text stays left aligned
+pub fn align_lines(lines: &[String], len: usize, align: &Alignment) -> Vec<String> {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| match align {
+            Alignment::Justify if i + 1 < lines.len() => justify(line, len),
+            _ => pad(line, len, align),
+        })
+        .collect()
+}
+
+/// Break `text` into lines of at most `line_len` columns.
+/// Words longer than a line are split between grapheme clusters.
+pub fn wrap_text(text: &str, line_len: usize) -> Vec<String> {
+    let mut result: Vec<String> = vec![];
+    let mut line = String::new();
+    let mut line_width = 0;
+
+    for word in text.split_whitespace() {
+        let word_width = width(word);
+        let space = usize::from(!line.is_empty());
+
+        // word fits on the current line
+        if line_width + space + word_width <= line_len {
+            if space == 1 {
+                line.push(' ');
+            }
+            line.push_str(word);
+            line_width += space + word_width;
+            continue;
+        }
+
+        // word fits on a line of its own
+        if word_width <= line_len {
+            result.push(std::mem::take(&mut line));
+            line.push_str(word);
+            line_width = word_width;
+            continue;
+        }
+
+        // word is longer than a line: fill the current line, then split the rest
+        if space == 1 && line_width + space < line_len {
+            line.push(' ');
+            line_width += 1;
+        } else if !line.is_empty() {
+            result.push(std::mem::take(&mut line));
+            line_width = 0;
+        }
+        for grapheme in word.graphemes(true) {
+            let grapheme_width = width(grapheme);
+            // always place at least one grapheme so a too narrow line cannot loop forever
+            if line_width + grapheme_width > line_len && line_width > 0 {
+                result.push(line.trim_end().to_string());
+                line.clear();
+                line_width = 0;
+            }
+            line.push_str(grapheme);
+            line_width += grapheme_width;
+        }
+    }
+    result.push(line);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_breaks_between_words() {
+        assert_eq!(wrap_text("a bb ccc", 4), vec!["a bb", "ccc"]);
+        assert_eq!(wrap_text("", 4), vec![""]);
+    }
+
+    #[test]
+    fn wrap_counts_wide_characters_twice() {
+        assert_eq!(
+            wrap_text("日本語 テキスト", 6),
+            vec!["日本語", "テキス", "ト"]
+        );
+        assert!(
+            wrap_text("日本語テキスト", 5)
+                .iter()
+                .all(|line| width(line) <= 5)
+        );
+    }
+
+    #[test]
+    fn wrap_keeps_combining_marks_with_their_letter() {
+        let word = "e\u{301}e\u{301}e\u{301}";
+        assert_eq!(wrap_text(word, 2), vec!["e\u{301}e\u{301}", "e\u{301}"]);
+    }
+
+    #[test]
+    fn wrap_too_narrow_line_terminates() {
+        assert_eq!(wrap_text("日本", 1), vec!["日", "本"]);
+        assert_eq!(wrap_text("abc", 0), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn pad_counts_columns() {
+        assert_eq!(pad("日本", 6, &Alignment::Right), "  日本");
+        assert_eq!(pad("ab", 5, &Alignment::Center), " ab  ");
+        assert_eq!(pad("e\u{301}", 3, &Alignment::Left), "e\u{301}  ");
+    }
+
+    #[test]
+    fn justify_spreads_leftmost_gaps_first() {
+        assert_eq!(justify("a b c", 8), "a   b  c");
+        assert_eq!(justify("日本 語", 9), "日本   語");
+        assert_eq!(justify("word", 6), "word  ");
+    }
+
+    #[test]
+    fn align_lines_keeps_last_justified_line_left() {
+        let lines = vec!["a b".to_string(), "c d".to_string()];
+        assert_eq!(
+            align_lines(&lines, 5, &Alignment::Justify),
+            vec!["a   b", "c d  "]
+        );
+    }
+
+    #[test]
+    fn repeat_cuts_between_graphemes() {
+        assert_eq!(repeat("-=", 5), "-=-=-");
+        // a wide character that doesn't fit leaves a space
+        assert_eq!(repeat("═日", 4), "═日═");
+        assert_eq!(repeat("日", 3), "日 ");
+        assert_eq!(repeat("e\u{301}", 2), "e\u{301}e\u{301}");
+        assert_eq!(repeat("\u{301}", 2), "  ");
+    }
+}
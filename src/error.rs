@@ -0,0 +1,90 @@
+use std::{fmt, io, path::Path};
+
+use console::style;
+
+/// Every failure of the CLI, each kind has its own exit code
+#[derive(Debug)]
+pub enum Error {
+    /// Reading or writing the data file or a source file
+    Io(String),
+    /// Invalid JSON/TOML/YAML in the data file or a spec, or a data file too new
+    Parse(String),
+    /// No template with this name
+    NotFound(String),
+    /// Invalid template or input values
+    Validation(String),
+    /// Clipboard not available
+    Clipboard(String),
+    /// A header could not be rendered
+    Render(String),
+    /// Prompt failed or not a terminal
+    Prompt(String),
+    /// `check` found files with a missing or stale header
+    Check(usize),
+}
+
+impl Error {
+    pub fn io(path: &Path, e: io::Error) -> Self {
+        Error::Io(format!("{}: {}", path.display(), e))
+    }
+
+    pub fn not_found(name: &str) -> Self {
+        Error::NotFound(name.to_string())
+    }
+
+    /// Print to stderr
+    pub fn report(&self) {
+        eprintln!("{}: {}", style("error").red().bold(), self);
+    }
+
+    /// Process exit code, see README
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Check(_) => 1,
+            Error::NotFound(_) => 3,
+            Error::Validation(_) => 4,
+            Error::Io(_) => 5,
+            Error::Parse(_) => 6,
+            Error::Clipboard(_) => 7,
+            Error::Render(_) => 8,
+            Error::Prompt(_) => 9,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Parse(e) => write!(f, "parse error: {}", e),
+            Error::NotFound(name) => write!(f, "`{}` not found", name),
+            Error::Validation(e) => write!(f, "{}", e),
+            Error::Clipboard(e) => write!(f, "clipboard: {}", e),
+            Error::Render(e) => write!(f, "render: {}", e),
+            Error::Prompt(e) => write!(f, "{}", e),
+            Error::Check(count) => write!(f, "{} files with a missing or stale header", count),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Parse(e.to_string())
+    }
+}
+
+impl From<dialoguer::Error> for Error {
+    fn from(e: dialoguer::Error) -> Self {
+        Error::Prompt(e.to_string())
+    }
+}
+
+impl From<arboard::Error> for Error {
+    fn from(e: arboard::Error) -> Self {
+        Error::Clipboard(e.to_string())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
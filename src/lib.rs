@@ -1,54 +1,128 @@
 use crate::{
-    commands::{Cli, Commands},
-    core::storage::Storage,
+    commands::{Cli, Commands, OutputArgs, StyleArgs},
+    core::{config::Config, storage::Storage},
 };
 use clap::Parser;
 use dialoguer::theme::ColorfulTheme;
 
+pub use crate::error::{Error, Result};
+
 mod commands;
 mod core;
+mod error;
 
-pub fn run() {
+pub fn run() -> Result<()> {
     let cli = Cli::parse();
-    let mut store = Storage::new();
     let input_theme = ColorfulTheme::default();
+    // config commands don't need a valid config or data file, they are used to repair them
+    if let Commands::Config { action } = &cli.command {
+        return commands::config::run(action.clone(), &input_theme);
+    }
+    let config = Config::load()?;
+    let output = |args: &OutputArgs| args.output(&cli.output, config.output);
+    let mut store = Storage::new()?;
 
     match cli.command {
-        Commands::New => {
-            commands::new::run(&mut store, &input_theme);
-        }
-        Commands::List { selection } => {
-            commands::list::run(selection, &store, &input_theme);
-        }
-        Commands::Get { name, json } => {
-            commands::get::run(name, json, &store);
-        }
+        Commands::New { from_file } => commands::new::run(from_file, &mut store, &input_theme),
+        Commands::List {
+            selection,
+            output: args,
+        } => commands::list::run(selection, &store, &output(&args), &input_theme),
+        Commands::Get {
+            name,
+            json,
+            style: StyleArgs { lang, comment },
+            output: args,
+        } => commands::get::run(name, json, lang, comment, &store, &output(&args)),
         Commands::Edit {
             header_name,
             name,
             spacing,
             border,
             fields,
-        } => {
-            commands::edit::run(
-                header_name,
-                name,
-                spacing,
-                border,
-                fields,
-                &mut store,
-                &input_theme,
-            );
-        }
-        Commands::Delete { all, name } => {
-            commands::delete::run(all, name, &mut store, &input_theme);
-        }
-        Commands::Default { values } => {
-            commands::default::run(values, &mut store);
-        }
-        Commands::Apply { name } => {
-            commands::apply::run(name, &mut store, &input_theme);
+            comment,
+            separator,
+            key_width,
+            from_file,
+        } => commands::edit::run(
+            header_name,
+            name,
+            spacing,
+            border,
+            fields,
+            comment,
+            separator,
+            key_width,
+            from_file,
+            &mut store,
+            &input_theme,
+        ),
+        Commands::Delete { all, name, yes } => {
+            commands::delete::run(all, name, yes, &mut store, &input_theme)
         }
-        Commands::Config => {}
+        Commands::Default {
+            values,
+            path,
+            style: StyleArgs { lang, comment },
+            output: args,
+        } => commands::default::run(values, path, lang, comment, &store, &output(&args)),
+        Commands::Apply {
+            name,
+            values,
+            from_file,
+            style: StyleArgs { lang, comment },
+            output: args,
+        } => commands::apply::run(
+            name,
+            values,
+            from_file,
+            lang,
+            comment,
+            &mut store,
+            &output(&args),
+            &input_theme,
+        ),
+        Commands::Insert {
+            name,
+            rules,
+            paths,
+            style: StyleArgs { lang, comment },
+        } => commands::insert::run(name, rules, paths, lang, comment, &store),
+        Commands::Update {
+            name,
+            rules,
+            paths,
+            dry_run,
+            style: StyleArgs { lang, comment },
+        } => commands::update::run(name, rules, paths, dry_run, lang, comment, &store),
+        Commands::Check {
+            paths,
+            name,
+            style: StyleArgs { lang, comment },
+        } => commands::check::run(paths, name, lang, comment, &store),
+        Commands::Fix {
+            paths,
+            name,
+            style: StyleArgs { lang, comment },
+        } => commands::fix::run(paths, name, lang, comment, &store),
+        Commands::License {
+            expr,
+            list,
+            name,
+            style: StyleArgs { lang, comment },
+            output: args,
+        } => commands::license::run(expr, list, name, lang, comment, &store, &output(&args)),
+        Commands::Export {
+            names,
+            output,
+            format,
+        } => commands::export::run(names, output, format, &store),
+        Commands::Import {
+            file,
+            overwrite,
+            rename,
+            skip,
+        } => commands::import::run(file, overwrite, rename, skip, &mut store),
+        Commands::Config { .. } => unreachable!("handled before loading the config"),
     }
 }
@@ -1,3 +1,8 @@
+use std::process;
+
 fn main() {
-    dev_header::run();
+    if let Err(e) = dev_header::run() {
+        e.report();
+        process::exit(e.exit_code());
+    }
 }